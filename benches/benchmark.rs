@@ -1,34 +1,127 @@
-use aoc_2019::days::{day1::Day1, day2::Day2, day3::Day3, day4::Day4, day5::Day5, Day};
+use aoc_2019::{
+    common::intcode::{self, assembler, image::Image, instruction_set::InstructionSet, Machine},
+    days::input::InputSource,
+    years::YEARS,
+};
 use criterion::*;
+use std::fs;
 
-fn criterion_benchmark(c: &mut Criterion) {
-    let day1_1 = Day1::new().unwrap();
-    let day1_2 = Day1::new().unwrap();
-    let day2_1 = Day2::new().unwrap();
-    let day2_2 = Day2::new().unwrap();
-    let day3_1 = Day3::new().unwrap();
-    let day3_2 = Day3::new().unwrap();
-    let day4_1 = Day4::new().unwrap();
-    let day4_2 = Day4::new().unwrap();
-    let day5_1 = Day5::new().unwrap();
-    let day5_2 = Day5::new().unwrap();
-
-    c.bench_function("day1/part1", move |b| b.iter(|| day1_1.part1()));
-    c.bench_function("day1/part2", move |b| b.iter(|| day1_2.part2()));
-    c.bench_function("day2/part1", move |b| b.iter(|| day2_1.part1()));
-    c.bench_function("day2/part2", move |b| b.iter(|| day2_2.part2()));
-    c.bench_function("day3/part1", move |b| b.iter(|| day3_1.part1()));
-    c.bench_function("day3/part2", move |b| b.iter(|| day3_2.part2()));
-    c.bench_function("day4/part1", move |b| b.iter(|| day4_1.part1()));
-    c.bench_function("day4/part2", move |b| b.iter(|| day4_2.part2()));
-    c.bench_function("day5/part1", move |b| b.iter(|| day5_1.part1()));
-    c.bench_function("day5/part2", move |b| b.iter(|| day5_2.part2()));
+/// Adds and a conditional jump, run 100,000 times.
+const COUNTDOWN: &str = "
+    loop: add [counter], -1, [counter]
+          jt [counter], loop
+          hlt
+    counter: data 100000
+";
+
+/// Sums 3i for i from 10,000 down to 1, a mix of every arithmetic instruction.
+const ARITHMETIC: &str = "
+    loop: mul [i], 3, [tmp]
+          add [tmp], [acc], [acc]
+          add [i], -1, [i]
+          lt 0, [i], [more]
+          jt [more], loop
+          hlt
+    i: data 10000
+    acc: data 0
+    tmp: data 0
+    more: data 0
+";
+
+/// Echoes its input until it reads a zero.
+const ECHO: &str = "
+    loop: in [value]
+          out [value]
+          jt [value], loop
+          hlt
+    value: data 0
+";
+
+const ECHO_INPUTS: i32 = 1000;
+
+/// Load, parse and both parts of every registered day, each in its own group so they
+/// can be compared across days.
+fn days_benchmark(c: &mut Criterion) {
+    for year in YEARS {
+        let source = InputSource::from_env_or(year.input_source());
+
+        for entry in year.days {
+            let id = format!("{}/day{}", year.year, entry.day);
+            let day = match (entry.construct)(&source) {
+                Ok(day) => day,
+                Err(e) => {
+                    eprintln!("Skipping {}: {:#}", id, e);
+
+                    continue;
+                }
+            };
+            let parsed = match day.parse_input() {
+                Ok(parsed) => parsed,
+                Err(e) => {
+                    eprintln!("Skipping {}: {:#}", id, e);
+
+                    continue;
+                }
+            };
+
+            c.benchmark_group("load")
+                .bench_function(&id, |b| b.iter(|| (entry.construct)(&source)));
+            c.benchmark_group("parse")
+                .bench_function(&id, |b| b.iter(|| day.parse_input()));
+
+            // Parts that aren't done yet would only measure how fast they give up
+            for part in [1, 2]
+                .iter()
+                .filter(|&&part| day.run_part(part, &parsed).is_ok())
+            {
+                c.benchmark_group(format!("part{}", part))
+                    .bench_function(&id, |b| b.iter(|| day.run_part(*part, &parsed)));
+            }
+        }
+    }
+}
+
+/// The interpreter on its own, on small programs that each lean on different
+/// instructions, and loading programs from text and images.
+fn intcode_benchmark(c: &mut Criterion) {
+    let instruction_set = InstructionSet::standard();
+    let countdown = assembler::assemble(COUNTDOWN, instruction_set).unwrap();
+    let arithmetic = assembler::assemble(ARITHMETIC, instruction_set).unwrap();
+    let echo = assembler::assemble(ECHO, instruction_set).unwrap();
+
+    let day5_text = fs::read_to_string("res/day5.txt").unwrap();
+    let day5_image = Image::new(intcode::parse_input_to_intcode(&day5_text).unwrap()).to_bytes();
+
+    let mut group = c.benchmark_group("intcode");
+
+    group.bench_function("countdown", |b| {
+        b.iter(|| Machine::new(countdown.clone()).run())
+    });
+    group.bench_function("arithmetic", |b| {
+        b.iter(|| Machine::new(arithmetic.clone()).run())
+    });
+    group.bench_function("echo", |b| {
+        b.iter(|| {
+            let mut machine = Machine::new(echo.clone());
+
+            for value in (0..ECHO_INPUTS).rev() {
+                machine.push_input(value);
+            }
+
+            machine.run()
+        })
+    });
+    group.bench_function("load_text", |b| {
+        b.iter(|| intcode::parse_input_to_intcode(&day5_text))
+    });
+    group.bench_function("load_image", |b| b.iter(|| Image::from_bytes(&day5_image)));
+    group.finish();
 }
 
 criterion_group!(
     name = benches;
     config = Criterion::default().sample_size(10);
-    targets = criterion_benchmark
+    targets = days_benchmark, intcode_benchmark
 );
 
 criterion_main!(benches);
@@ -0,0 +1,192 @@
+use anyhow::Result;
+use aoc_2019::common::intcode::{
+    self, coverage::Coverage, disassembler, image::Image, instruction_set::InstructionSet,
+    symbols::Symbols, trace::Tracer, Machine,
+};
+use std::{
+    fs::{self, File},
+    io,
+    path::{Path, PathBuf},
+};
+use structopt::StructOpt;
+
+#[derive(Debug, StructOpt)]
+#[structopt(name = "intcode")]
+enum Command {
+    /// Runs a program once per --input and shows what each run executed
+    Coverage {
+        #[structopt(parse(from_os_str))]
+        program: PathBuf,
+        /// Comma separated inputs for one run, repeat for more runs
+        #[structopt(short, long, required = true)]
+        input: Vec<String>,
+        /// Write an HTML report here instead of printing a listing
+        #[structopt(long, parse(from_os_str))]
+        html: Option<PathBuf>,
+        /// Symbols file, defaults to the .sym next to the program
+        #[structopt(short, long, parse(from_os_str))]
+        symbols: Option<PathBuf>,
+    },
+    /// Converts a comma separated program to a binary image, or an image back to text.
+    /// Labels travel between the image and the .sym next to the text program.
+    Convert {
+        #[structopt(parse(from_os_str))]
+        input: PathBuf,
+        #[structopt(parse(from_os_str))]
+        output: PathBuf,
+    },
+    /// Prints a program as source, with labels and comments from its symbols
+    Disassemble {
+        #[structopt(parse(from_os_str))]
+        program: PathBuf,
+        /// Print source the assembler accepts, without addresses
+        #[structopt(long)]
+        source: bool,
+        /// Symbols file, defaults to the .sym next to the program
+        #[structopt(short, long, parse(from_os_str))]
+        symbols: Option<PathBuf>,
+    },
+    /// Runs a program, printing every instruction it executes
+    Trace {
+        #[structopt(parse(from_os_str))]
+        program: PathBuf,
+        /// Comma separated inputs
+        #[structopt(short, long, default_value = "")]
+        input: String,
+        /// Symbols file, defaults to the .sym next to the program
+        #[structopt(short, long, parse(from_os_str))]
+        symbols: Option<PathBuf>,
+    },
+}
+
+fn main() -> Result<()> {
+    match Command::from_args() {
+        Command::Coverage {
+            program,
+            input,
+            html,
+            symbols,
+        } => {
+            let (intcode, symbols) = read_program(&program, symbols.as_deref())?;
+            let mut coverage = Coverage::new(&intcode, InstructionSet::standard());
+            coverage.symbols = symbols;
+
+            for run_input in input {
+                let inputs = parse_inputs(&run_input)?;
+
+                // A failed run still shows how far it got in the report
+                match coverage.run(&format!("input {}", run_input), &inputs) {
+                    Ok(outputs) => println!("input {} -> {:?}", run_input, outputs),
+                    Err(e) => println!("input {} -> error: {}", run_input, e),
+                }
+            }
+
+            match html {
+                Some(path) => fs::write(path, coverage.html())?,
+                None => print!("{}", coverage.listing()),
+            }
+        }
+        Command::Convert { input, output } => {
+            let bytes = fs::read(&input)?;
+
+            if Image::is_image(&bytes) {
+                let image = Image::from_bytes(&bytes)?;
+                let text = image
+                    .memory
+                    .iter()
+                    .map(|word| word.to_string())
+                    .collect::<Vec<String>>()
+                    .join(",");
+
+                fs::write(&output, text)?;
+
+                if !image.symbols.is_empty() {
+                    let mut symbols = Symbols::load_for_program(&output)?;
+                    symbols.merge(&Symbols {
+                        labels: image.symbols,
+                        ..Symbols::default()
+                    });
+                    symbols.save(&Symbols::sidecar_path(&output))?;
+                }
+            } else {
+                let (memory, symbols) = read_program(&input, None)?;
+                let image = Image {
+                    memory,
+                    symbols: symbols.labels,
+                };
+
+                image.write_to(File::create(output)?)?;
+            }
+        }
+        Command::Disassemble {
+            program,
+            source,
+            symbols,
+        } => {
+            let (intcode, symbols) = read_program(&program, symbols.as_deref())?;
+            let lines = disassembler::disassemble_with_symbols(
+                &intcode,
+                InstructionSet::standard(),
+                &symbols,
+            );
+
+            if source {
+                print!("{}", disassembler::source(&lines, &symbols));
+            } else {
+                print!("{}", disassembler::listing(&lines, &symbols));
+            }
+        }
+        Command::Trace {
+            program,
+            input,
+            symbols,
+        } => {
+            let (intcode, symbols) = read_program(&program, symbols.as_deref())?;
+            let mut machine = Machine::new(intcode);
+            let mut tracer = Tracer::new(io::stdout(), &symbols);
+
+            parse_inputs(&input)?
+                .into_iter()
+                .for_each(|input| machine.push_input(input));
+
+            let state = machine.run_with_observer(&mut tracer)?;
+            tracer.finish()?;
+
+            println!("{:?} with outputs {:?}", state, machine.take_outputs());
+        }
+    }
+
+    Ok(())
+}
+
+/// Reads either a comma separated program or a binary image, along with its symbols:
+/// any labels stored in the image, overridden by the given symbols file or else the
+/// program's sidecar.
+fn read_program(path: &Path, symbols: Option<&Path>) -> Result<(Vec<i32>, Symbols)> {
+    let bytes = fs::read(path)?;
+    let mut program_symbols = Symbols::default();
+
+    let intcode = if Image::is_image(&bytes) {
+        let image = Image::from_bytes(&bytes)?;
+        program_symbols.labels = image.symbols;
+
+        image.memory
+    } else {
+        intcode::parse_input_to_intcode(String::from_utf8(bytes)?.trim())?
+    };
+
+    program_symbols.merge(&match symbols {
+        Some(symbols) => Symbols::load(symbols)?,
+        None => Symbols::load_for_program(path)?,
+    });
+
+    Ok((intcode, program_symbols))
+}
+
+fn parse_inputs(inputs: &str) -> Result<Vec<i32>> {
+    if inputs.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    Ok(intcode::parse_input_to_intcode(inputs)?)
+}
@@ -1,37 +1,543 @@
-use anyhow::Result;
-use aoc_2019::day_runner::DayRunner;
-use std::io;
+use anyhow::{anyhow, Result};
+use aoc_2019::{
+    day_runner::{
+        answers::{Answers, Verdict},
+        measure::{CountingAllocator, MeasureConfig},
+        output::{self, OutputFormat},
+        perf::{self, Timings},
+        print_day,
+        watch::{self, Watched},
+        DayRunner, RunSummary, RunnerError,
+    },
+    days::{examples, input::InputSource},
+    scaffold::{self, ScaffoldError},
+    site::{
+        fetch::Fetcher,
+        submit::{Outcome, Submitter},
+        Site,
+    },
+    tui,
+    years::{self, Year, YearError},
+};
+use std::{
+    env,
+    io::{self, IsTerminal},
+    path::{Path, PathBuf},
+    process::ExitCode,
+    thread,
+};
 use structopt::StructOpt;
 
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+/// A day or example errored, or something else went wrong.
+const EXIT_FAILURE: u8 = 1;
+/// The arguments don't make sense, e.g. a day that isn't done yet.
+const EXIT_USAGE: u8 = 2;
+/// Everything ran, but an answer is known to be wrong.
+const EXIT_WRONG_ANSWER: u8 = 3;
+/// A part got slower between the revisions compared.
+const EXIT_SLOWER: u8 = 4;
+
 #[derive(Debug, StructOpt)]
-#[structopt(name = "args")]
+#[structopt(
+    name = "args",
+    after_help = "Exits with 1 if something fails, 2 for invalid arguments, 3 when an \
+                  answer is wrong and 4 when compare finds a part that got slower"
+)]
 struct Args {
+    /// Which year's puzzles to work on
+    #[structopt(short, long, default_value = "2019")]
+    year: u32,
     #[structopt(short, long)]
     day: Option<u32>,
     #[structopt(short, long, requires = "day")]
     part: Option<u32>,
+    /// Run every implemented day and print a summary table
+    #[structopt(short, long, conflicts_with = "day")]
+    all: bool,
+    /// Runs of each part to throw away before measuring
+    #[structopt(long, default_value = "0")]
+    warmup: u32,
+    /// Runs of each part to average the time over
+    #[structopt(long, default_value = "1")]
+    repetitions: u32,
+    /// Parts to run at once, 0 for one per core. Peak allocation is only measured, and
+    /// timings only kept for compare, with 1
+    #[structopt(short, long, default_value = "0")]
+    jobs: usize,
+    /// How to print results: text, json, csv or markdown
+    #[structopt(short, long, default_value = "text")]
+    format: OutputFormat,
+    /// Puzzle input: a file, a directory of dayN.txt files, or - for stdin. A file or
+    /// stdin needs --day. Defaults to $AOC_INPUT, then the year's directory, e.g. res
+    #[structopt(short, long, parse(from_str = InputSource::parse))]
+    input: Option<InputSource>,
+    /// Known answers to check results against. Defaults to answers.json in the year's
+    /// directory
+    #[structopt(long, parse(from_os_str))]
+    answers: Option<PathBuf>,
+    /// Save answers that aren't in the answers file yet, once you've checked them
+    #[structopt(long)]
+    record: bool,
+    /// Submit the answer to the puzzle site, using the session token in $AOC_SESSION
+    #[structopt(long, requires_all = &["day", "part"])]
+    submit: bool,
+    /// Timings of every run by git revision, for compare. Defaults to timings.json in
+    /// the year's directory
+    #[structopt(long, parse(from_os_str))]
+    timings: Option<PathBuf>,
+    /// Don't add this run's timings to the timings file
+    #[structopt(long)]
+    no_timings: bool,
+    /// Answers submitted so far. Defaults to submissions.json in the year's directory
+    #[structopt(long, parse(from_os_str))]
+    history: Option<PathBuf>,
+    /// Run the worked examples in the year's examples directory instead of the puzzle
+    /// inputs, for --day or every day
+    #[structopt(long, conflicts_with = "all")]
+    examples: bool,
+    /// Run --day again whenever its input or examples file changes, comparing each run
+    /// with the one before
+    #[structopt(
+        long,
+        requires = "day",
+        conflicts_with_all = &["examples", "record", "submit"]
+    )]
+    watch: bool,
+    #[structopt(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Debug, StructOpt)]
+enum Command {
+    /// Downloads puzzle inputs into the input directory, using the session token in
+    /// $AOC_SESSION. Inputs that are already there are left alone
+    Fetch {
+        #[structopt(required = true)]
+        days: Vec<u32>,
+    },
+    /// Adds a day from the template: its module, its line in the days! registry and
+    /// empty input and examples files
+    NewDay { day: u32 },
+    /// Compares the timings of two revisions, flagging parts that got slower. Defaults
+    /// to the latest revision run and the one before it
+    Compare {
+        base: Option<String>,
+        head: Option<String>,
+        /// How many percent slower a part can get before it's flagged
+        #[structopt(long, default_value = "10")]
+        threshold: f64,
+    },
+}
+
+fn main() -> ExitCode {
+    let args = match Args::from_iter_safe(env::args_os()) {
+        Ok(args) => args,
+        Err(e) if e.use_stderr() => {
+            eprintln!("{}", e.message);
+
+            return ExitCode::from(EXIT_USAGE);
+        }
+        Err(e) => {
+            println!("{}", e.message);
+
+            return ExitCode::SUCCESS;
+        }
+    };
+
+    match run(args) {
+        Ok(code) => ExitCode::from(code),
+        Err(e) => {
+            eprintln!("error: {:#}", e);
+
+            ExitCode::from(exit_code(&e))
+        }
+    }
 }
 
-fn main() -> Result<()> {
-    let args = Args::from_args();
-    let day_runner = DayRunner::new()?;
+fn exit_code(error: &anyhow::Error) -> u8 {
+    let usage = error.downcast_ref::<RunnerError>().is_some()
+        || error.downcast_ref::<YearError>().is_some()
+        || matches!(
+            error.downcast_ref::<ScaffoldError>(),
+            Some(ScaffoldError::InvalidDay(_))
+        );
+
+    if usage {
+        EXIT_USAGE
+    } else {
+        EXIT_FAILURE
+    }
+}
+
+/// Failures win over wrong answers, since a failed part has no answer to check.
+fn summary_code(summary: &RunSummary) -> u8 {
+    if summary.has_failures() {
+        EXIT_FAILURE
+    } else if summary.has_wrong_answers() {
+        EXIT_WRONG_ANSWER
+    } else {
+        0
+    }
+}
+
+/// Does what the arguments ask, returning the exit code.
+fn run(args: Args) -> Result<u8> {
+    let year = years::find(args.year)?;
+    let input = args
+        .input
+        .unwrap_or_else(|| InputSource::from_env_or(year.input_source()));
+    let answers_path = args.answers.unwrap_or_else(|| year.answers_path());
+    let history_path = args.history.unwrap_or_else(|| year.history_path());
+    let timings_path = args.timings.unwrap_or_else(|| year.timings_path());
+
+    match &args.command {
+        Some(Command::Fetch { days }) => {
+            fetch(year, days, input)?;
+
+            return Ok(0);
+        }
+        Some(Command::NewDay { day }) => {
+            for path in scaffold::new_day(Path::new("."), year, *day)? {
+                println!("Wrote {}", path.display());
+            }
+
+            return Ok(0);
+        }
+        Some(Command::Compare {
+            base,
+            head,
+            threshold,
+        }) => {
+            return compare(&timings_path, base.as_deref(), head.as_deref(), *threshold);
+        }
+        None => {}
+    }
+
+    if args.examples {
+        return run_examples(year, args.day);
+    }
+
+    let config = MeasureConfig {
+        warmup: args.warmup,
+        repetitions: args.repetitions,
+    };
+
+    if let (true, Some(day)) = (args.watch, args.day) {
+        let jobs = args.jobs;
+        let runner = |input| -> Result<DayRunner> {
+            Ok(DayRunner::with_year(year, config, input, Some(day))?
+                .with_answers(Answers::load(&answers_path)?)
+                .with_jobs(jobs))
+        };
+
+        watch_day(year, day, args.part, input, runner)?;
+
+        return Ok(0);
+    }
+
+    let day_runner = DayRunner::with_year(year, config, input, args.day)?
+        .with_answers(Answers::load(&answers_path)?)
+        .with_jobs(args.jobs);
+
+    let summary = if args.all {
+        let summary = day_runner.run_all();
+        print!("{}", output::render(&summary, args.format));
+
+        summary
+    } else if let Some(day) = args.day {
+        let summary = day_runner.run_selected(day, args.part)?;
+
+        if args.format == OutputFormat::Text {
+            print_day(day, &summary);
+        } else {
+            print!("{}", output::render(&summary, args.format));
+        }
+
+        summary
+    } else {
+        if io::stdout().is_terminal() {
+            tui::run(&day_runner)?;
+        } else {
+            run_interactive(&day_runner)?;
+        }
+
+        return Ok(0);
+    };
+
+    // Parts running alongside each other slow each other down
+    if !args.no_timings && args.jobs == 1 {
+        let mut timings = Timings::load(&timings_path)?;
+        timings.record(&perf::current_revision(), config, &summary);
+        timings.save(&timings_path)?;
+    }
+
+    if args.record {
+        record_answers(&answers_path, &summary)?;
+    }
+
+    if args.submit && !submit_answers(year, &answers_path, &history_path, &summary)? {
+        return Ok(EXIT_WRONG_ANSWER);
+    }
+
+    Ok(summary_code(&summary))
+}
+
+fn run_examples(year: &Year, day: Option<u32>) -> Result<u8> {
+    let entries = year
+        .days
+        .iter()
+        .filter(|entry| day.is_none_or(|day| day == entry.day));
+    let results = examples::run_all(&year.examples_dir(), entries)?;
+    let failed = results.iter().filter(|result| !result.passed()).count();
+
+    for result in &results {
+        let example = &result.example;
+        let outcome = match &result.answer {
+            _ if result.passed() => "ok".to_string(),
+            Ok(answer) => format!("FAILED, expected {} but got {}", example.expected, answer),
+            Err(e) => format!(
+                "FAILED, expected {} but got error: {:#}",
+                example.expected, e
+            ),
+        };
+
+        println!(
+            "day {} part {} (line {}): {}",
+            example.day, example.part, example.line, outcome
+        );
+    }
 
-    if let Some(day) = args.day {
-        day_runner.run_day(day, args.part);
+    println!("{} examples, {} failed", results.len(), failed);
 
-        return Ok(());
+    if failed > 0 {
+        return Ok(EXIT_WRONG_ANSWER);
     }
 
+    Ok(0)
+}
+
+/// Runs the day, then again each time its input or examples change, until it's
+/// interrupted. The runner is rebuilt every time so that the input is read again.
+fn watch_day<F>(
+    year: &Year,
+    day: u32,
+    part: Option<u32>,
+    input: InputSource,
+    runner: F,
+) -> Result<()>
+where
+    F: Fn(InputSource) -> Result<DayRunner>,
+{
+    let input_path = input
+        .path_for(day)
+        .ok_or_else(|| anyhow!("Only an input file or directory can be watched"))?;
+    let entry = year
+        .days
+        .iter()
+        .find(|entry| entry.day == day)
+        .ok_or(RunnerError::NotImplemented(day))?;
+    let examples_path = examples::examples_path(&year.examples_dir(), day);
+    let mut watched = Watched::new(vec![input_path, examples_path]);
+    let mut previous = None;
+
     loop {
         println!();
-        println!("Which day should I run? (1-25 or q to quit)");
-        let mut input = String::new();
+        println!("========== DAY {} ==========", day);
+
+        match runner(input.clone()).and_then(|runner| runner.run_selected(day, part)) {
+            Ok(summary) => {
+                for line in watch::compare(previous.as_ref(), &summary) {
+                    println!("{}", line);
+                }
+
+                previous = Some(summary);
+            }
+            Err(e) => eprintln!("error: {:#}", e),
+        }
+
+        match examples::load(&year.examples_dir(), day) {
+            Ok(day_examples) => {
+                let results = day_examples
+                    .iter()
+                    .filter(|example| part.is_none_or(|part| part == example.part))
+                    .map(|example| examples::run(entry, example))
+                    .collect::<Vec<examples::ExampleResult>>();
+                let failed = results.iter().filter(|result| !result.passed()).count();
+
+                if !results.is_empty() {
+                    println!(
+                        "Examples: {} passed, {} failed",
+                        results.len() - failed,
+                        failed
+                    );
+                }
+            }
+            Err(e) => eprintln!("error: {}", e),
+        }
+
+        let paths = watched
+            .paths()
+            .iter()
+            .map(|path| path.display().to_string())
+            .collect::<Vec<String>>();
+        println!("Watching {} for changes...", paths.join(" and "));
+
+        while !watched.changed() {
+            thread::sleep(watch::POLL_INTERVAL);
+        }
+    }
+}
+
+fn compare(path: &Path, base: Option<&str>, head: Option<&str>, threshold: f64) -> Result<u8> {
+    let timings = Timings::load(path)?;
+    let (base, head, comparisons) = timings.compare(base, head, threshold)?;
+    let slower = comparisons.iter().filter(|part| part.slower).count();
+
+    println!("Comparing {} with {}", head.revision, base.revision);
+    println!(
+        "{:>3}  {:>4}  {:>10}  {:>10}  {:>8}",
+        "Day", "Part", "Base", "Head", "Change"
+    );
+
+    for part in &comparisons {
+        println!(
+            "{:>3}  {:>4}  {:>10}  {:>10}  {:>+7.1}%{}",
+            part.day,
+            part.part,
+            format!("{:.2?}", part.base),
+            format!("{:.2?}", part.head),
+            part.change(),
+            if part.slower { "  slower" } else { "" }
+        );
+    }
+
+    println!(
+        "{} of {} parts more than {}% slower",
+        slower,
+        comparisons.len(),
+        threshold
+    );
+
+    if slower > 0 {
+        return Ok(EXIT_SLOWER);
+    }
+
+    Ok(0)
+}
+
+fn fetch(year: &Year, days: &[u32], input: InputSource) -> Result<()> {
+    let cache_dir = match input {
+        InputSource::Directory(dir) => dir,
+        _ => return Err(anyhow!("Inputs can only be fetched into a directory")),
+    };
+    let fetcher = Fetcher::new(Site::from_env_for_year(year.year)?, &cache_dir);
+
+    for &day in days {
+        if fetcher.is_cached(day) {
+            println!(
+                "Day {} is already in {}",
+                day,
+                fetcher.cache_path(day).display()
+            );
+        } else {
+            fetcher.fetch(day)?;
+            println!(
+                "Fetched day {} into {}",
+                day,
+                fetcher.cache_path(day).display()
+            );
+        }
+    }
+
+    Ok(())
+}
 
-        io::stdin()
-            .read_line(&mut input)
-            .expect("Failed to read input");
+/// Submits each answer that isn't already known to be correct, saving the ones the
+/// site accepts in the answers file. Returns whether every answer sent was right.
+/// What the site says goes to stderr, so it doesn't end up in json or csv output.
+fn submit_answers(
+    year: &Year,
+    answers_path: &Path,
+    history_path: &Path,
+    summary: &RunSummary,
+) -> Result<bool> {
+    let mut submitter = Submitter::new(Site::from_env_for_year(year.year)?, history_path)?;
+    let mut answers = Answers::load(answers_path)?;
+    let mut all_correct = true;
+
+    for result in &summary.parts {
+        let answer = match (&result.answer, &result.verdict) {
+            (Ok(_), Verdict::Correct) => {
+                eprintln!("Day {} part {} is already solved", result.day, result.part);
+
+                continue;
+            }
+            (Ok(answer), _) if !answer.is_submittable() => {
+                eprintln!(
+                    "Day {} part {} is a drawing, read it and submit the letters by hand:\n{}",
+                    result.day, result.part, answer
+                );
+
+                continue;
+            }
+            (Ok(answer), _) => answer,
+            (Err(_), _) => continue,
+        };
+
+        let outcome = submitter.submit(result.day, result.part, &answer.to_string())?;
+        eprintln!("{}", outcome);
+        all_correct &= !outcome.is_wrong();
+
+        if outcome == Outcome::Correct {
+            answers.record(
+                result.day,
+                result.part,
+                result.input_hash.as_deref(),
+                answer,
+            );
+            answers.save(answers_path)?;
+        }
+    }
+
+    Ok(all_correct)
+}
+
+/// Adds answers the file doesn't know about yet, keyed by the input they're for.
+fn record_answers(path: &Path, summary: &RunSummary) -> Result<()> {
+    let mut answers = Answers::load(path)?;
+    let mut recorded = 0;
+
+    for result in &summary.parts {
+        if let (Ok(answer), Verdict::Unknown) = (&result.answer, &result.verdict) {
+            answers.record(
+                result.day,
+                result.part,
+                result.input_hash.as_deref(),
+                answer,
+            );
+            recorded += 1;
+        }
+    }
+
+    answers.save(path)?;
+    eprintln!("Recorded {} new answers in {}", recorded, path.display());
+
+    Ok(())
+}
+
+fn run_interactive(day_runner: &DayRunner) -> Result<()> {
+    loop {
+        println!();
+        println!(
+            "Which day should I run? (1-{} or q to quit)",
+            day_runner.puzzle_days()
+        );
+        let mut input = String::new();
 
-        if input.trim() == "q" {
+        if io::stdin().read_line(&mut input)? == 0 || input.trim() == "q" {
             println!("Later!");
 
             return Ok(());
@@ -40,7 +546,7 @@ fn main() -> Result<()> {
         let day = match input.trim().parse::<u32>() {
             Ok(num) => num,
             Err(_) => {
-                println!("That's... not a valid day");
+                eprintln!("That's... not a valid day");
 
                 continue;
             }
@@ -1 +1,4 @@
 pub mod intcode;
+pub mod json_file;
+#[cfg(test)]
+pub(crate) mod test_dir;
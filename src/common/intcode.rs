@@ -1,15 +1,16 @@
-use std::convert::TryFrom;
+pub mod assembler;
+pub mod async_machine;
+pub mod coverage;
+pub mod disassembler;
+pub mod image;
+pub mod instruction_set;
+pub mod symbols;
+pub mod trace;
+
+use instruction_set::{Action, Instruction, InstructionSet, Parameter, MAX_PARAMETERS};
+use std::{collections::VecDeque, convert::TryFrom};
 use thiserror::Error;
 
-const OPCODE_ADD: i32 = 1;
-const OPCODE_MULTIPLY: i32 = 2;
-const OPCODE_INPUT: i32 = 3;
-const OPCODE_OUTPUT: i32 = 4;
-const OPCODE_JUMP_IF_TRUE: i32 = 5;
-const OPCODE_JUMP_IF_FALSE: i32 = 6;
-const OPCODE_LESS_THAN: i32 = 7;
-const OPCODE_EQUALS: i32 = 8;
-const OPCODE_HALT: i32 = 99;
 const MODE_POSITION: i32 = 0;
 const MODE_IMMEDIATE: i32 = 1;
 
@@ -35,27 +36,71 @@ pub enum IntcodeError {
 
     #[error("Unknown Mode: {0}")]
     UnknownMode(i32),
-}
 
-#[derive(Debug)]
-enum Opcode {
-    Add([Mode; 3]),
-    Multiply([Mode; 3]),
-    Input([Mode; 1]),
-    Output([Mode; 1]),
-    JumpIfTrue([Mode; 2]),
-    JumpIfFalse([Mode; 2]),
-    LessThan([Mode; 3]),
-    Equals([Mode; 3]),
-    Halt,
+    #[error("Address out of range: {0}")]
+    AddressOutOfRange(i32),
+
+    #[error("Opcode must be between 1 and 99: {0}")]
+    InvalidOpcode(i32),
+
+    #[error("Opcode {0} is already registered")]
+    DuplicateOpcode(i32),
+
+    #[error("Mnemonic {0} is already registered")]
+    DuplicateMnemonic(String),
+
+    #[error(
+        "Instruction {0} has {1} parameters, the maximum is {}",
+        MAX_PARAMETERS
+    )]
+    TooManyParameters(String, usize),
+
+    #[error("Failed to send output: {0}")]
+    OutputSinkError(String),
 }
 
-#[derive(Debug)]
-enum Mode {
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Mode {
     Position,
     Immediate,
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum State {
+    Running,
+    AwaitingInput,
+    Halted,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Access {
+    Read(usize),
+    Write(usize),
+}
+
+/// A completed instruction, along with the data it read and wrote. `words` is the
+/// instruction as it was when it started, `memory` is after it finished.
+pub struct Step<'s> {
+    pub address: usize,
+    pub instruction: &'s Instruction,
+    pub words: &'s [i32],
+    pub accesses: &'s [Access],
+    pub memory: &'s [i32],
+}
+
+pub trait Observer {
+    fn on_step(&mut self, step: &Step);
+}
+
+pub struct Machine<'a> {
+    memory: Vec<i32>,
+    pointer: usize,
+    inputs: VecDeque<i32>,
+    outputs: Vec<i32>,
+    instruction_set: &'a InstructionSet,
+    accesses: Option<Vec<Access>>,
+}
+
 impl TryFrom<i32> for Mode {
     type Error = IntcodeError;
 
@@ -68,332 +113,278 @@ impl TryFrom<i32> for Mode {
     }
 }
 
-pub fn parse_input_to_intcode(input: &str) -> Result<Vec<i32>, IntcodeError> {
-    input
-        .split(",")
-        .map(|slice| {
-            slice
-                .parse::<i32>()
-                .map_err(|_| IntcodeError::IntcodeParseError(slice.to_string()))
-        })
-        .collect::<Result<Vec<i32>, IntcodeError>>()
+impl From<Mode> for i32 {
+    fn from(mode: Mode) -> Self {
+        match mode {
+            Mode::Position => MODE_POSITION,
+            Mode::Immediate => MODE_IMMEDIATE,
+        }
+    }
 }
 
-pub fn run_intcode_to_halt(
-    intcode: &mut Vec<i32>,
-    input: Option<i32>,
-) -> Result<Vec<i32>, IntcodeError> {
-    if intcode.len() == 0 {
-        return Err(IntcodeError::UnexpectedEndOfIntcode);
+impl Machine<'static> {
+    pub fn new(memory: Vec<i32>) -> Self {
+        Machine::with_instruction_set(memory, InstructionSet::standard())
     }
-
-    let mut pointer = 0;
-    let mut outputs = Vec::new();
-    while run_intcode(intcode, &mut pointer, input, &mut outputs)? {}
-
-    Ok(outputs)
 }
 
-fn run_intcode(
-    intcode: &mut Vec<i32>,
-    pointer: &mut usize,
-    input: Option<i32>,
-    output: &mut Vec<i32>,
-) -> Result<bool, IntcodeError> {
-    if *pointer >= intcode.len() {
-        return Err(IntcodeError::UnexpectedEndOfIntcode);
+impl<'a> Machine<'a> {
+    pub fn with_instruction_set(memory: Vec<i32>, instruction_set: &'a InstructionSet) -> Self {
+        Machine {
+            memory,
+            pointer: 0,
+            inputs: VecDeque::new(),
+            outputs: Vec::new(),
+            instruction_set,
+            accesses: None,
+        }
     }
 
-    let instruction = parse_instruction(intcode[*pointer])?;
-
-    if let Opcode::Halt = instruction {
-        return Ok(false);
+    pub fn memory(&self) -> &[i32] {
+        &self.memory
     }
 
-    match instruction {
-        Opcode::Add(modes) => run_add(intcode, pointer, modes),
-        Opcode::Multiply(modes) => run_mult(intcode, pointer, modes),
-        Opcode::Input(modes) => run_input(intcode, pointer, modes, input),
-        Opcode::Output(modes) => run_output(intcode, pointer, modes, output),
-        Opcode::JumpIfTrue(modes) => run_jump_if_true(intcode, pointer, modes),
-        Opcode::JumpIfFalse(modes) => run_jump_if_false(intcode, pointer, modes),
-        Opcode::LessThan(modes) => run_less_than(intcode, pointer, modes),
-        Opcode::Equals(modes) => run_equals(intcode, pointer, modes),
-        _ => Err(IntcodeError::UnknownOpcode(intcode[*pointer])),
-    }?;
-
-    Ok(true)
-}
-
-fn parse_instruction(instruction: i32) -> Result<Opcode, IntcodeError> {
-    if instruction < 0 {
-        return Err(IntcodeError::NegativeInstruction);
+    pub fn into_memory(self) -> Vec<i32> {
+        self.memory
     }
 
-    let opcode = instruction % 100;
-    let mode_1 = Mode::try_from(instruction % 1_000 / 100)?; // hundreds
-    let mode_2 = Mode::try_from(instruction % 10_000 / 1_000)?; // thousands
-    let mode_3 = Mode::try_from(instruction % 100_000 / 10_000)?; // ten thousands
-
-    match opcode {
-        OPCODE_ADD => Ok(Opcode::Add([mode_1, mode_2, mode_3])),
-        OPCODE_MULTIPLY => Ok(Opcode::Multiply([mode_1, mode_2, mode_3])),
-        OPCODE_INPUT => Ok(Opcode::Input([mode_1])),
-        OPCODE_OUTPUT => Ok(Opcode::Output([mode_1])),
-        OPCODE_JUMP_IF_TRUE => Ok(Opcode::JumpIfTrue([mode_1, mode_2])),
-        OPCODE_JUMP_IF_FALSE => Ok(Opcode::JumpIfFalse([mode_1, mode_2])),
-        OPCODE_LESS_THAN => Ok(Opcode::LessThan([mode_1, mode_2, mode_3])),
-        OPCODE_EQUALS => Ok(Opcode::Equals([mode_1, mode_2, mode_3])),
-        OPCODE_HALT => Ok(Opcode::Halt),
-        _ => Err(IntcodeError::UnknownOpcode(opcode)),
+    pub fn pointer(&self) -> usize {
+        self.pointer
     }
-}
 
-fn run_add(
-    intcode: &mut Vec<i32>,
-    pointer: &mut usize,
-    modes: [Mode; 3],
-) -> Result<(), IntcodeError> {
-    if (*pointer + 3) >= intcode.len() {
-        return Err(IntcodeError::UnexpectedEndOfIntcode.into());
+    pub fn push_input(&mut self, value: i32) {
+        self.inputs.push_back(value);
     }
 
-    let input1 = match modes[0] {
-        Mode::Position => intcode[intcode[*pointer + 1] as usize],
-        Mode::Immediate => intcode[*pointer + 1 as usize],
-    };
+    pub fn pop_input(&mut self) -> Option<i32> {
+        self.inputs.pop_front()
+    }
 
-    let input2 = match modes[1] {
-        Mode::Position => intcode[intcode[*pointer + 2] as usize],
-        Mode::Immediate => intcode[*pointer + 2 as usize],
-    };
+    pub fn push_output(&mut self, value: i32) {
+        self.outputs.push(value);
+    }
 
-    match modes[2] {
-        Mode::Position => {
-            let output_address = intcode[*pointer + 3] as usize;
-            intcode[output_address] = input1 + input2;
-        }
-        Mode::Immediate => intcode[*pointer + 3 as usize] = input1 + input2,
+    pub fn take_outputs(&mut self) -> Vec<i32> {
+        std::mem::take(&mut self.outputs)
     }
 
-    *pointer += 4;
+    pub fn read(&mut self, address: i32) -> Result<i32, IntcodeError> {
+        let index = self.index(address)?;
 
-    Ok(())
-}
+        if let Some(accesses) = &mut self.accesses {
+            accesses.push(Access::Read(index));
+        }
 
-fn run_mult(
-    intcode: &mut Vec<i32>,
-    pointer: &mut usize,
-    modes: [Mode; 3],
-) -> Result<(), IntcodeError> {
-    if (*pointer + 3) >= intcode.len() {
-        return Err(IntcodeError::UnexpectedEndOfIntcode.into());
+        Ok(self.memory[index])
     }
 
-    let input1 = match modes[0] {
-        Mode::Position => intcode[intcode[*pointer + 1] as usize],
-        Mode::Immediate => intcode[*pointer + 1 as usize],
-    };
+    pub fn write(&mut self, address: i32, value: i32) -> Result<(), IntcodeError> {
+        let index = self.index(address)?;
 
-    let input2 = match modes[1] {
-        Mode::Position => intcode[intcode[*pointer + 2] as usize],
-        Mode::Immediate => intcode[*pointer + 2 as usize],
-    };
-
-    match modes[2] {
-        Mode::Position => {
-            let output_address = intcode[*pointer + 3] as usize;
-            intcode[output_address] = input1 * input2;
+        if let Some(accesses) = &mut self.accesses {
+            accesses.push(Access::Write(index));
         }
-        Mode::Immediate => intcode[*pointer + 3 as usize] = input1 * input2,
-    }
 
-    *pointer += 4;
+        self.memory[index] = value;
 
-    Ok(())
-}
+        Ok(())
+    }
 
-fn run_input(
-    intcode: &mut Vec<i32>,
-    pointer: &mut usize,
-    modes: [Mode; 1],
-    input: Option<i32>,
-) -> Result<(), IntcodeError> {
-    if (*pointer + 1) >= intcode.len() {
-        return Err(IntcodeError::UnexpectedEndOfIntcode);
+    fn index(&self, address: i32) -> Result<usize, IntcodeError> {
+        usize::try_from(address)
+            .ok()
+            .filter(|index| *index < self.memory.len())
+            .ok_or(IntcodeError::AddressOutOfRange(address))
     }
 
-    if input.is_none() {
-        return Err(IntcodeError::NoInputFound);
+    /// Runs until the program halts or needs an input that hasn't been pushed yet.
+    pub fn run(&mut self) -> Result<State, IntcodeError> {
+        loop {
+            match self.step()? {
+                State::Running => continue,
+                state => return Ok(state),
+            }
+        }
     }
 
-    match modes[0] {
-        Mode::Position => {
-            let output_address = intcode[*pointer + 1] as usize;
-            intcode[output_address] = input.unwrap();
+    /// Same as `run`, but tells the observer about every instruction that completes.
+    pub fn run_with_observer(
+        &mut self,
+        observer: &mut dyn Observer,
+    ) -> Result<State, IntcodeError> {
+        self.accesses = Some(Vec::new());
+
+        let result = loop {
+            let address = self.pointer;
+            let mut words = [0; MAX_PARAMETERS + 1];
+            // A jump can leave the program, which `execute` reports
+            let rest = self.memory.get(address..).unwrap_or_default();
+            let available = rest.len().min(words.len());
+            words[..available].copy_from_slice(&rest[..available]);
+
+            match self.execute() {
+                Ok((state, instruction)) => {
+                    if state != State::AwaitingInput {
+                        observer.on_step(&Step {
+                            address,
+                            instruction,
+                            words: &words[..=instruction.parameters.len()],
+                            accesses: self.accesses.as_deref().unwrap_or_default(),
+                            memory: &self.memory,
+                        });
+                    }
+
+                    if state != State::Running {
+                        break Ok(state);
+                    }
+                }
+                Err(e) => break Err(e),
+            }
+
+            if let Some(accesses) = &mut self.accesses {
+                accesses.clear();
+            }
+        };
+
+        self.accesses = None;
+
+        result
+    }
 
-            Ok(())
-        }
-        Mode::Immediate => {
-            intcode[*pointer + 1] = input.unwrap();
+    pub fn step(&mut self) -> Result<State, IntcodeError> {
+        self.execute().map(|(state, _)| state)
+    }
 
-            Ok(())
+    fn execute(&mut self) -> Result<(State, &'a Instruction), IntcodeError> {
+        if self.pointer >= self.memory.len() {
+            return Err(IntcodeError::UnexpectedEndOfIntcode);
         }
-    }?;
-
-    *pointer += 2;
 
-    Ok(())
-}
+        let instruction_set = self.instruction_set;
+        let word = self.memory[self.pointer];
+        let (instruction, modes) = instruction_set.decode(word)?;
+        let arity = instruction.parameters.len();
 
-fn run_output(
-    intcode: &mut Vec<i32>,
-    pointer: &mut usize,
-    modes: [Mode; 1],
-    outputs: &mut Vec<i32>,
-) -> Result<(), IntcodeError> {
-    if (*pointer + 1) >= intcode.len() {
-        return Err(IntcodeError::UnexpectedEndOfIntcode);
-    }
+        if self.pointer + arity >= self.memory.len() {
+            return Err(IntcodeError::UnexpectedEndOfIntcode);
+        }
 
-    match modes[0] {
-        Mode::Position => outputs.push(intcode[intcode[*pointer + 1] as usize]),
-        Mode::Immediate => outputs.push(intcode[*pointer + 1]),
-    };
+        let mut params = [0; MAX_PARAMETERS];
 
-    *pointer += 2;
+        for (i, parameter) in instruction.parameters.iter().enumerate() {
+            let address = self.pointer + 1 + i;
+            let raw = self.memory[address];
 
-    Ok(())
-}
+            params[i] = match (parameter, modes[i]) {
+                (Parameter::Read, Mode::Position) | (Parameter::Jump, Mode::Position) => {
+                    self.read(raw)?
+                }
+                (Parameter::Read, Mode::Immediate) | (Parameter::Jump, Mode::Immediate) => raw,
+                (Parameter::Write, Mode::Position) => raw,
+                (Parameter::Write, Mode::Immediate) => address as i32,
+            };
+        }
 
-fn run_jump_if_true(
-    intcode: &mut Vec<i32>,
-    pointer: &mut usize,
-    modes: [Mode; 2],
-) -> Result<(), IntcodeError> {
-    if (*pointer + 1) >= intcode.len() {
-        return Err(IntcodeError::UnexpectedEndOfIntcode);
-    }
+        let state = match (instruction.handler)(self, &params[..arity])? {
+            Action::Next => {
+                self.pointer += 1 + arity;
 
-    let is_true = match modes[0] {
-        Mode::Position => intcode[intcode[*pointer + 1] as usize] != 0,
-        Mode::Immediate => intcode[*pointer + 1] != 0,
-    };
+                State::Running
+            }
+            Action::Jump(target) => {
+                self.pointer =
+                    usize::try_from(target).map_err(|_| IntcodeError::AddressOutOfRange(target))?;
 
-    if !is_true {
-        *pointer += 3;
+                State::Running
+            }
+            Action::AwaitInput => State::AwaitingInput,
+            Action::Halt => State::Halted,
+        };
 
-        return Ok(());
+        Ok((state, instruction))
     }
+}
 
-    match modes[1] {
-        Mode::Position => {
-            *pointer = intcode[intcode[*pointer + 2] as usize] as usize;
-        }
-        Mode::Immediate => {
-            *pointer = intcode[*pointer + 2] as usize;
-        }
-    };
-
-    Ok(())
+pub fn parse_input_to_intcode(input: &str) -> Result<Vec<i32>, IntcodeError> {
+    input
+        .split(',')
+        .map(|slice| {
+            slice
+                .parse::<i32>()
+                .map_err(|_| IntcodeError::IntcodeParseError(slice.to_string()))
+        })
+        .collect::<Result<Vec<i32>, IntcodeError>>()
 }
 
-fn run_jump_if_false(
-    intcode: &mut Vec<i32>,
-    pointer: &mut usize,
-    modes: [Mode; 2],
-) -> Result<(), IntcodeError> {
-    if (*pointer + 2) >= intcode.len() {
+/// Runs the program until it halts, giving it `input` every time it asks for one.
+pub fn run_intcode_to_halt(
+    intcode: &mut [i32],
+    input: Option<i32>,
+) -> Result<Vec<i32>, IntcodeError> {
+    if intcode.is_empty() {
         return Err(IntcodeError::UnexpectedEndOfIntcode);
     }
 
-    let is_false = match modes[0] {
-        Mode::Position => intcode[intcode[*pointer + 1] as usize] == 0,
-        Mode::Immediate => intcode[*pointer + 1] == 0,
-    };
+    let mut machine = Machine::new(intcode.to_vec());
 
-    if !is_false {
-        *pointer += 3;
-
-        return Ok(());
-    }
-
-    match modes[1] {
-        Mode::Position => {
-            *pointer = intcode[intcode[*pointer + 2] as usize] as usize;
-        }
-        Mode::Immediate => {
-            *pointer = intcode[*pointer + 2] as usize;
+    let result = loop {
+        match machine.run() {
+            Ok(State::AwaitingInput) => match input {
+                Some(input) => machine.push_input(input),
+                None => break Err(IntcodeError::NoInputFound),
+            },
+            Ok(_) => break Ok(machine.take_outputs()),
+            Err(e) => break Err(e),
         }
     };
+    intcode.copy_from_slice(machine.memory());
 
-    Ok(())
+    result
 }
 
-fn run_less_than(
-    intcode: &mut Vec<i32>,
-    pointer: &mut usize,
-    modes: [Mode; 3],
-) -> Result<(), IntcodeError> {
-    if (*pointer + 3) >= intcode.len() {
-        return Err(IntcodeError::UnexpectedEndOfIntcode);
-    }
+#[cfg(test)]
+mod intcode_tests {
+    use super::*;
+    use instruction_set::Instruction;
 
-    let input1 = match modes[0] {
-        Mode::Position => intcode[intcode[*pointer + 1] as usize],
-        Mode::Immediate => intcode[*pointer + 1 as usize],
-    };
+    #[test]
+    fn compare_to_eight() -> Result<(), IntcodeError> {
+        let program = parse_input_to_intcode("3,3,1108,-1,8,3,4,3,99")?;
 
-    let input2 = match modes[1] {
-        Mode::Position => intcode[intcode[*pointer + 2] as usize],
-        Mode::Immediate => intcode[*pointer + 2 as usize],
-    };
+        assert_eq!(run_intcode_to_halt(&mut program.clone(), Some(8))?, vec![1]);
+        assert_eq!(run_intcode_to_halt(&mut program.clone(), Some(7))?, vec![0]);
 
-    let output = if input1 < input2 { 1 } else { 0 };
+        // Every input instruction reads the same input
+        let program = parse_input_to_intcode("3,0,3,1,4,0,4,1,99")?;
+        assert_eq!(
+            run_intcode_to_halt(&mut program.clone(), Some(5))?,
+            vec![5, 5]
+        );
+        assert!(run_intcode_to_halt(&mut program.clone(), None).is_err());
 
-    match modes[2] {
-        Mode::Position => {
-            let output_address = intcode[*pointer + 3] as usize;
-            intcode[output_address] = output;
-        }
-        Mode::Immediate => intcode[*pointer + 3 as usize] = output,
+        Ok(())
     }
 
-    *pointer += 4;
+    #[test]
+    fn custom_opcode() -> Result<(), IntcodeError> {
+        let mut instruction_set = InstructionSet::standard().clone();
+        instruction_set.register(Instruction::new(
+            42,
+            "dbl",
+            &[Parameter::Read, Parameter::Write],
+            |machine, params| {
+                machine.write(params[1], params[0] * 2)?;
 
-    Ok(())
-}
+                Ok(Action::Next)
+            },
+        ))?;
 
-fn run_equals(
-    intcode: &mut Vec<i32>,
-    pointer: &mut usize,
-    modes: [Mode; 3],
-) -> Result<(), IntcodeError> {
-    if (*pointer + 3) >= intcode.len() {
-        return Err(IntcodeError::UnexpectedEndOfIntcode);
-    }
+        let mut machine =
+            Machine::with_instruction_set(vec![142, 21, 7, 4, 7, 99, 0, 0], &instruction_set);
 
-    let input1 = match modes[0] {
-        Mode::Position => intcode[intcode[*pointer + 1] as usize],
-        Mode::Immediate => intcode[*pointer + 1 as usize],
-    };
+        assert_eq!(machine.run()?, State::Halted);
+        assert_eq!(machine.take_outputs(), vec![42]);
 
-    let input2 = match modes[1] {
-        Mode::Position => intcode[intcode[*pointer + 2] as usize],
-        Mode::Immediate => intcode[*pointer + 2 as usize],
-    };
-
-    let output = if input1 == input2 { 1 } else { 0 };
-
-    match modes[2] {
-        Mode::Position => {
-            let output_address = intcode[*pointer + 3] as usize;
-            intcode[output_address] = output;
-        }
-        Mode::Immediate => intcode[*pointer + 3 as usize] = output,
+        Ok(())
     }
-
-    *pointer += 4;
-
-    Ok(())
 }
@@ -0,0 +1,236 @@
+use super::{instruction_set::InstructionSet, Mode};
+use std::collections::HashMap;
+use thiserror::Error;
+
+const DATA_DIRECTIVE: &str = "data";
+
+#[derive(Debug, Error)]
+pub enum AssemblerError {
+    #[error("Line {0}: unknown mnemonic {1}")]
+    UnknownMnemonic(usize, String),
+
+    #[error("Line {0}: {1} takes {2} operands, found {3}")]
+    WrongOperandCount(usize, String, usize, usize),
+
+    #[error("Line {0}: invalid operand {1}")]
+    InvalidOperand(usize, String),
+
+    #[error("Line {0}: invalid label {1}")]
+    InvalidLabel(usize, String),
+
+    #[error("Line {0}: label {1} is already defined")]
+    DuplicateLabel(usize, String),
+
+    #[error("Line {0}: unknown label {1}")]
+    UnknownLabel(usize, String),
+}
+
+struct Statement<'a> {
+    line_number: usize,
+    mnemonic: &'a str,
+    operands: Vec<&'a str>,
+}
+
+/// Assembles source in the form produced by the disassembler. Each line is an optional
+/// `label:`, then either `data` or a mnemonic followed by comma separated operands.
+/// Operands are numbers or labels, wrapped in `[]` for position mode. `;` starts a comment.
+pub fn assemble(
+    source: &str,
+    instruction_set: &InstructionSet,
+) -> Result<Vec<i32>, AssemblerError> {
+    let mut labels = HashMap::new();
+    let mut statements = Vec::new();
+    let mut address = 0;
+
+    for (i, line) in source.lines().enumerate() {
+        let line_number = i + 1;
+        let mut line = line.split(';').next().unwrap_or("").trim();
+
+        if let Some(colon) = line.find(':') {
+            let label = line[..colon].trim();
+
+            if !is_label(label) {
+                return Err(AssemblerError::InvalidLabel(line_number, label.to_string()));
+            }
+
+            if labels.insert(label, address as i32).is_some() {
+                return Err(AssemblerError::DuplicateLabel(
+                    line_number,
+                    label.to_string(),
+                ));
+            }
+
+            line = line[colon + 1..].trim();
+        }
+
+        if line.is_empty() {
+            continue;
+        }
+
+        let (mnemonic, operands) = match line.find(char::is_whitespace) {
+            Some(space) => (&line[..space], line[space..].trim()),
+            None => (line, ""),
+        };
+
+        let operands = if operands.is_empty() {
+            Vec::new()
+        } else {
+            operands.split(',').map(str::trim).collect::<Vec<&str>>()
+        };
+
+        address += if mnemonic == DATA_DIRECTIVE {
+            operands.len()
+        } else {
+            let instruction = instruction_set.get_by_mnemonic(mnemonic).ok_or_else(|| {
+                AssemblerError::UnknownMnemonic(line_number, mnemonic.to_string())
+            })?;
+
+            if instruction.parameters.len() != operands.len() {
+                return Err(AssemblerError::WrongOperandCount(
+                    line_number,
+                    mnemonic.to_string(),
+                    instruction.parameters.len(),
+                    operands.len(),
+                ));
+            }
+
+            operands.len() + 1
+        };
+
+        statements.push(Statement {
+            line_number,
+            mnemonic,
+            operands,
+        });
+    }
+
+    let mut intcode = Vec::with_capacity(address);
+
+    for statement in statements {
+        let operands = statement
+            .operands
+            .iter()
+            .map(|operand| parse_operand(operand, &labels, statement.line_number))
+            .collect::<Result<Vec<(Mode, i32)>, AssemblerError>>()?;
+
+        if statement.mnemonic == DATA_DIRECTIVE {
+            for (mode, value) in operands {
+                if mode != Mode::Immediate {
+                    return Err(AssemblerError::InvalidOperand(
+                        statement.line_number,
+                        format!("[{}]", value),
+                    ));
+                }
+
+                intcode.push(value);
+            }
+        } else {
+            // Checked in the first pass
+            let instruction = instruction_set.get_by_mnemonic(statement.mnemonic).unwrap();
+            let modes = operands
+                .iter()
+                .map(|(mode, _)| *mode)
+                .collect::<Vec<Mode>>();
+
+            intcode.push(instruction.encode(&modes));
+            intcode.extend(operands.into_iter().map(|(_, value)| value));
+        }
+    }
+
+    Ok(intcode)
+}
+
+fn parse_operand(
+    operand: &str,
+    labels: &HashMap<&str, i32>,
+    line_number: usize,
+) -> Result<(Mode, i32), AssemblerError> {
+    let (mode, value) = if operand.starts_with('[') && operand.ends_with(']') {
+        (Mode::Position, operand[1..operand.len() - 1].trim())
+    } else {
+        (Mode::Immediate, operand)
+    };
+
+    if let Ok(value) = value.parse::<i32>() {
+        return Ok((mode, value));
+    }
+
+    if !is_label(value) {
+        return Err(AssemblerError::InvalidOperand(
+            line_number,
+            operand.to_string(),
+        ));
+    }
+
+    labels
+        .get(value)
+        .map(|address| (mode, *address))
+        .ok_or_else(|| AssemblerError::UnknownLabel(line_number, value.to_string()))
+}
+
+pub(crate) fn is_label(label: &str) -> bool {
+    let mut chars = label.chars();
+
+    match chars.next() {
+        Some(first) if first.is_ascii_alphabetic() || first == '_' => {
+            chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod assembler_tests {
+    use super::*;
+    use crate::common::intcode::{self, disassembler, symbols::Symbols};
+    use anyhow::Result;
+    use std::{fs, path::Path};
+
+    #[test]
+    fn labels() -> Result<()> {
+        let source = "
+            in [value]      ; read a number
+            out [value]
+            jt 1, end
+            data 7
+            end: hlt
+            value: data 0
+        ";
+
+        let intcode = assemble(source, InstructionSet::standard())?;
+
+        assert_eq!(intcode, vec![3, 9, 4, 9, 1105, 1, 8, 7, 99, 0]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn disassembly_round_trip() -> Result<()> {
+        let instruction_set = InstructionSet::standard();
+        let intcode = intcode::parse_input_to_intcode(&fs::read_to_string("res/day5.txt")?)?;
+
+        let source = disassembler::disassemble(&intcode, instruction_set)
+            .iter()
+            .map(|line| format!("{}\n", line))
+            .collect::<String>();
+
+        assert_eq!(assemble(&source, instruction_set)?, intcode);
+
+        Ok(())
+    }
+
+    #[test]
+    fn labelled_disassembly_round_trip() -> Result<()> {
+        let instruction_set = InstructionSet::standard();
+        let intcode = intcode::parse_input_to_intcode(&fs::read_to_string("res/day5.txt")?)?;
+        let symbols = Symbols::load_for_program(Path::new("res/day5.txt"))?;
+
+        let lines = disassembler::disassemble_with_symbols(&intcode, instruction_set, &symbols);
+        let source = disassembler::source(&lines, &symbols);
+
+        assert!(source.contains("in [scratch]"));
+        assert_eq!(assemble(&source, instruction_set)?, intcode);
+
+        Ok(())
+    }
+}
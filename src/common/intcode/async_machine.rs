@@ -0,0 +1,119 @@
+use super::{IntcodeError, Machine, State};
+use futures::{Sink, SinkExt, Stream, StreamExt};
+use std::fmt::Display;
+
+impl<'a> Machine<'a> {
+    /// Runs to halt, awaiting each input from `inputs` and sending each output to
+    /// `outputs` as soon as it's produced. Doesn't depend on any particular executor.
+    pub async fn run_async<I, O>(
+        &mut self,
+        mut inputs: I,
+        mut outputs: O,
+    ) -> Result<(), IntcodeError>
+    where
+        I: Stream<Item = i32> + Unpin,
+        O: Sink<i32> + Unpin,
+        O::Error: Display,
+    {
+        loop {
+            let state = self.step()?;
+
+            for output in self.take_outputs() {
+                outputs
+                    .send(output)
+                    .await
+                    .map_err(|e| IntcodeError::OutputSinkError(e.to_string()))?;
+            }
+
+            match state {
+                State::Running => continue,
+                State::AwaitingInput => match inputs.next().await {
+                    Some(input) => self.push_input(input),
+                    None => return Err(IntcodeError::NoInputFound),
+                },
+                State::Halted => return Ok(()),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod async_machine_tests {
+    use super::*;
+    use crate::common::intcode;
+    use anyhow::Result;
+    use futures::{channel::mpsc, executor::LocalPool, task::LocalSpawnExt};
+    use std::{cell::Cell, rc::Rc};
+
+    /// Runs one machine per phase on a single thread, each feeding the next. With
+    /// `feedback` the last machine's outputs also go back to the first.
+    fn run_amplifiers(program: &str, phases: &[i32], feedback: bool) -> Result<Option<i32>> {
+        let intcode = intcode::parse_input_to_intcode(program)?;
+        let mut pool = LocalPool::new();
+        let (senders, receivers): (Vec<_>, Vec<_>) =
+            (0..=phases.len()).map(|_| mpsc::unbounded::<i32>()).unzip();
+        let mut receivers = receivers.into_iter();
+
+        for (sender, phase) in senders.iter().zip(phases) {
+            sender.unbounded_send(*phase)?;
+        }
+
+        senders[0].unbounded_send(0)?;
+
+        for sender in senders.iter().skip(1) {
+            let mut machine = Machine::new(intcode.clone());
+            let input = receivers.next().unwrap();
+            let output = sender.clone();
+
+            pool.spawner().spawn_local(async move {
+                machine.run_async(input, output).await.unwrap();
+            })?;
+        }
+
+        let last_output = Rc::new(Cell::new(None));
+        let mut final_outputs = receivers.next().unwrap();
+        let first_input = senders[0].clone();
+        let last = Rc::clone(&last_output);
+
+        pool.spawner().spawn_local(async move {
+            while let Some(output) = final_outputs.next().await {
+                last.set(Some(output));
+
+                if feedback {
+                    // The first machine may already have halted
+                    let _ = first_input.unbounded_send(output);
+                }
+            }
+        })?;
+
+        drop(senders);
+        pool.run();
+
+        Ok(last_output.get())
+    }
+
+    #[test]
+    fn amplifier_chain() -> Result<()> {
+        let program = "3,15,3,16,1002,16,10,16,1,16,15,15,4,15,99,0,0";
+
+        assert_eq!(
+            run_amplifiers(program, &[4, 3, 2, 1, 0], false)?,
+            Some(43210)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn amplifier_feedback_loop() -> Result<()> {
+        let program = "3,26,1001,26,-4,26,3,27,1002,27,2,27,1,27,26,27,\
+                       4,27,1001,28,-1,28,1005,28,6,99,0,0,5";
+
+        assert_eq!(
+            run_amplifiers(program, &[9, 8, 7, 6, 5], true)?,
+            Some(139629729)
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,262 @@
+use super::{
+    disassembler::{self, Line, Operation},
+    instruction_set::InstructionSet,
+    symbols::Symbols,
+    Access, IntcodeError, Machine, Observer, State, Step,
+};
+
+/// Execution and data access counts per address for a single run of a program.
+#[derive(Clone, Debug)]
+pub struct RunCoverage {
+    pub name: String,
+    pub executed: Vec<u64>,
+    pub reads: Vec<u64>,
+    pub writes: Vec<u64>,
+}
+
+/// Coverage collected over any number of runs of the same program.
+pub struct Coverage<'a> {
+    intcode: Vec<i32>,
+    instruction_set: &'a InstructionSet,
+    pub symbols: Symbols,
+    pub runs: Vec<RunCoverage>,
+}
+
+impl RunCoverage {
+    fn new(name: &str, size: usize) -> Self {
+        RunCoverage {
+            name: name.to_string(),
+            executed: vec![0; size],
+            reads: vec![0; size],
+            writes: vec![0; size],
+        }
+    }
+
+    fn is_executed(&self, line: &Line) -> bool {
+        self.executed[line.address] > 0
+    }
+
+    fn is_read(&self, line: &Line) -> bool {
+        (line.address..line.address + line.words.len()).any(|address| self.reads[address] > 0)
+    }
+
+    fn is_written(&self, line: &Line) -> bool {
+        (line.address..line.address + line.words.len()).any(|address| self.writes[address] > 0)
+    }
+
+    /// Short marker for a line: its execution count, or which data accesses touched it.
+    fn marker(&self, line: &Line) -> String {
+        if self.is_executed(line) {
+            return format!("{}", self.executed[line.address]);
+        }
+
+        match (self.is_read(line), self.is_written(line)) {
+            (true, true) => "rw".to_string(),
+            (true, false) => "r".to_string(),
+            (false, true) => "w".to_string(),
+            (false, false) => ".".to_string(),
+        }
+    }
+}
+
+impl Observer for RunCoverage {
+    fn on_step(&mut self, step: &Step) {
+        self.executed[step.address] += 1;
+
+        for access in step.accesses {
+            match access {
+                Access::Read(address) => self.reads[*address] += 1,
+                Access::Write(address) => self.writes[*address] += 1,
+            }
+        }
+    }
+}
+
+impl<'a> Coverage<'a> {
+    pub fn new(intcode: &[i32], instruction_set: &'a InstructionSet) -> Self {
+        Coverage {
+            intcode: intcode.to_vec(),
+            instruction_set,
+            symbols: Symbols::default(),
+            runs: Vec::new(),
+        }
+    }
+
+    /// Runs a fresh copy of the program to halt with the given inputs, recording
+    /// its coverage under `name`. Returns the program's outputs. A run that fails
+    /// still keeps the coverage it got up to.
+    pub fn run(&mut self, name: &str, inputs: &[i32]) -> Result<Vec<i32>, IntcodeError> {
+        let mut run = RunCoverage::new(name, self.intcode.len());
+        let mut machine = Machine::with_instruction_set(self.intcode.clone(), self.instruction_set);
+
+        inputs.iter().for_each(|input| machine.push_input(*input));
+
+        let state = machine.run_with_observer(&mut run);
+        self.runs.push(run);
+
+        match state? {
+            State::Halted => Ok(machine.take_outputs()),
+            _ => Err(IntcodeError::NoInputFound),
+        }
+    }
+
+    pub fn lines(&self) -> Vec<Line> {
+        disassembler::disassemble_with_symbols(&self.intcode, self.instruction_set, &self.symbols)
+    }
+
+    /// Disassembly of the program with a column per run. A number is how many times
+    /// the instruction executed, `r`/`w` mark words accessed as data, `.` is untouched.
+    pub fn listing(&self) -> String {
+        const MIN_COLUMN_WIDTH: usize = 6;
+
+        let lines = self.lines();
+        let mut listing = String::new();
+        let column_width = self
+            .runs
+            .iter()
+            .map(|run| run.name.len() + 2)
+            .fold(MIN_COLUMN_WIDTH, usize::max);
+
+        for run in &self.runs {
+            listing.push_str(&format!("{:<width$}", run.name, width = column_width));
+        }
+
+        listing.push_str(&format!("{:>5}  code\n", "addr"));
+        listing.push_str(&disassembler::render(&lines, &self.symbols, |line| {
+            let markers = self
+                .runs
+                .iter()
+                .map(|run| format!("{:<width$}", run.marker(line), width = column_width))
+                .collect::<String>();
+
+            format!("{}{:>5}  ", markers, line.address)
+        }));
+        listing.push('\n');
+        listing.push_str(&self.summary(&lines));
+
+        listing
+    }
+
+    /// Standalone HTML page with the same information as `listing`, coloured by how
+    /// many runs executed each instruction.
+    pub fn html(&self) -> String {
+        let lines = self.lines();
+        let mut html = String::from(
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>Intcode coverage</title>\n\
+             <style>\n\
+             body { font-family: monospace; }\n\
+             td, th { padding: 0 0.75em; text-align: left; }\n\
+             .all { background: #c8f7c5; }\n\
+             .some { background: #fbeeb8; }\n\
+             .none { background: #f7c5c5; }\n\
+             .data { background: #c5dcf7; }\n\
+             </style>\n</head>\n<body>\n<table>\n<tr>",
+        );
+
+        for run in &self.runs {
+            html.push_str(&format!("<th>{}</th>", escape_html(&run.name)));
+        }
+
+        html.push_str("<th>addr</th><th>label</th><th>code</th><th>comment</th></tr>\n");
+
+        for annotated in disassembler::annotate(&lines, &self.symbols) {
+            let line = annotated.line;
+            let executed_by = self.runs.iter().filter(|run| run.is_executed(line)).count();
+            let accessed = self
+                .runs
+                .iter()
+                .any(|run| run.is_read(line) || run.is_written(line));
+
+            let class = match (&line.operation, executed_by) {
+                (_, n) if n > 0 && n == self.runs.len() => "all",
+                (_, n) if n > 0 => "some",
+                (_, _) if accessed => "data",
+                (Operation::Instruction { .. }, _) => "none",
+                (Operation::Data(_), _) => "",
+            };
+
+            html.push_str(&format!("<tr class=\"{}\">", class));
+
+            for run in &self.runs {
+                html.push_str(&format!("<td>{}</td>", run.marker(line)));
+            }
+
+            html.push_str(&format!(
+                "<td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>\n",
+                line.address,
+                escape_html(annotated.label.as_deref().unwrap_or("")),
+                escape_html(&annotated.text),
+                escape_html(annotated.comment.as_deref().unwrap_or(""))
+            ));
+        }
+
+        html.push_str("</table>\n<pre>\n");
+        html.push_str(&escape_html(&self.summary(&lines)));
+        html.push_str("</pre>\n</body>\n</html>\n");
+
+        html
+    }
+
+    fn summary(&self, lines: &[Line]) -> String {
+        let instructions = lines
+            .iter()
+            .filter(|line| matches!(line.operation, Operation::Instruction { .. }))
+            .count();
+
+        self.runs
+            .iter()
+            .map(|run| {
+                let executed = lines.iter().filter(|line| run.is_executed(line)).count();
+
+                format!(
+                    "{}: executed {} of {} instructions\n",
+                    run.name, executed, instructions
+                )
+            })
+            .collect()
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod coverage_tests {
+    use super::*;
+    use crate::common::intcode;
+    use anyhow::Result;
+    use std::fs;
+
+    #[test]
+    fn day5_diagnostic_branches() -> Result<()> {
+        let intcode = intcode::parse_input_to_intcode(&fs::read_to_string("res/day5.txt")?)?;
+        let mut coverage = Coverage::new(&intcode, InstructionSet::standard());
+
+        assert_eq!(coverage.run("input 1", &[1])?.pop(), Some(15386262));
+        assert_eq!(coverage.run("input 5", &[5])?.pop(), Some(10376124));
+
+        let only_in = |this: &RunCoverage, other: &RunCoverage| {
+            (0..intcode.len())
+                .any(|address| this.executed[address] > 0 && other.executed[address] == 0)
+        };
+
+        assert!(only_in(&coverage.runs[0], &coverage.runs[1]));
+        assert!(only_in(&coverage.runs[1], &coverage.runs[0]));
+        assert!(coverage.listing().contains("input 1: executed"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn keeps_failed_runs() {
+        let mut coverage = Coverage::new(&[1101, 1, 1, 5, 77, 0], InstructionSet::standard());
+
+        assert!(coverage.run("bad opcode", &[]).is_err());
+        assert_eq!(coverage.runs.len(), 1);
+        assert_eq!(coverage.runs[0].executed[0], 1);
+    }
+}
@@ -0,0 +1,255 @@
+use super::{
+    instruction_set::{Instruction, InstructionSet, Parameter},
+    symbols::Symbols,
+    Mode,
+};
+use std::{
+    collections::HashSet,
+    fmt::{self, Display, Formatter},
+};
+
+#[derive(Clone, Debug)]
+pub struct Line {
+    pub address: usize,
+    pub words: Vec<i32>,
+    pub operation: Operation,
+}
+
+#[derive(Clone, Debug)]
+pub enum Operation {
+    Instruction {
+        mnemonic: String,
+        operands: Vec<Operand>,
+    },
+    Data(i32),
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Operand {
+    pub parameter: Parameter,
+    pub mode: Mode,
+    pub value: i32,
+}
+
+impl Display for Line {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{}", self.text(None))
+    }
+}
+
+impl Line {
+    /// The line as source. With labels, operands that refer to a labelled address
+    /// use the label instead.
+    fn text(&self, labels: Option<&Labels>) -> String {
+        match &self.operation {
+            Operation::Instruction { mnemonic, operands } => {
+                let operands = operands
+                    .iter()
+                    .map(|operand| {
+                        let label = labels.and_then(|labels| labels.for_operand(operand));
+
+                        match (operand.mode, label) {
+                            (Mode::Position, Some(label)) => format!("[{}]", label),
+                            (Mode::Position, None) => format!("[{}]", operand.value),
+                            (Mode::Immediate, Some(label)) => label.to_string(),
+                            (Mode::Immediate, None) => format!("{}", operand.value),
+                        }
+                    })
+                    .collect::<Vec<String>>()
+                    .join(", ");
+
+                if operands.is_empty() {
+                    mnemonic.to_string()
+                } else {
+                    format!("{} {}", mnemonic, operands)
+                }
+            }
+            Operation::Data(value) => format!("data {}", value),
+        }
+    }
+}
+
+/// Labels that can be used in source, i.e. those that land on the start of a line.
+struct Labels<'a> {
+    symbols: &'a Symbols,
+    line_starts: HashSet<usize>,
+}
+
+impl<'a> Labels<'a> {
+    fn new(lines: &[Line], symbols: &'a Symbols) -> Self {
+        Labels {
+            symbols,
+            line_starts: lines.iter().map(|line| line.address).collect(),
+        }
+    }
+
+    fn get(&self, address: usize) -> Option<&'a str> {
+        if self.line_starts.contains(&address) {
+            self.symbols.label(address)
+        } else {
+            None
+        }
+    }
+
+    fn for_operand(&self, operand: &Operand) -> Option<&'a str> {
+        let refers_to_address = operand.mode == Mode::Position
+            || (operand.mode == Mode::Immediate && operand.parameter == Parameter::Jump);
+
+        if refers_to_address && operand.value >= 0 {
+            self.get(operand.value as usize)
+        } else {
+            None
+        }
+    }
+}
+
+/// Linear sweep over the whole program. Words that don't decode cleanly into an
+/// instruction from the set are emitted as `data`.
+pub fn disassemble(intcode: &[i32], instruction_set: &InstructionSet) -> Vec<Line> {
+    disassemble_with_symbols(intcode, instruction_set, &Symbols::default())
+}
+
+/// Same as `disassemble`, but words in the symbols' data regions are always `data`.
+pub fn disassemble_with_symbols(
+    intcode: &[i32],
+    instruction_set: &InstructionSet,
+    symbols: &Symbols,
+) -> Vec<Line> {
+    let mut lines = Vec::new();
+    let mut address = 0;
+
+    while address < intcode.len() {
+        let line =
+            decode_line(intcode, address, instruction_set, symbols).unwrap_or_else(|| Line {
+                address,
+                words: vec![intcode[address]],
+                operation: Operation::Data(intcode[address]),
+            });
+
+        address += line.words.len();
+        lines.push(line);
+    }
+
+    lines
+}
+
+/// Renders lines with their addresses, one per row, along with any labels and comments.
+pub fn listing(lines: &[Line], symbols: &Symbols) -> String {
+    render(lines, symbols, |line| format!("{:>5}  ", line.address))
+}
+
+/// Renders lines as source that the assembler accepts, using labels and comments
+/// from the symbols.
+pub fn source(lines: &[Line], symbols: &Symbols) -> String {
+    render(lines, symbols, |_| String::new())
+}
+
+/// A line with its label, its text using labels, and its comments.
+pub struct Annotated<'l> {
+    pub line: &'l Line,
+    pub label: Option<String>,
+    pub text: String,
+    pub comment: Option<String>,
+}
+
+pub fn annotate<'l>(lines: &'l [Line], symbols: &Symbols) -> Vec<Annotated<'l>> {
+    let labels = Labels::new(lines, symbols);
+
+    lines
+        .iter()
+        .map(|line| {
+            let comments = (line.address..line.address + line.words.len())
+                .filter_map(|address| symbols.comment(address))
+                .collect::<Vec<&str>>();
+
+            Annotated {
+                line,
+                label: labels.get(line.address).map(str::to_string),
+                text: line.text(Some(&labels)),
+                comment: if comments.is_empty() {
+                    None
+                } else {
+                    Some(comments.join("; "))
+                },
+            }
+        })
+        .collect()
+}
+
+/// Shared by everything that shows disassembled lines as text: each line gets a
+/// prefix, then its label if it has one, its text and its comment.
+pub fn render<F>(lines: &[Line], symbols: &Symbols, mut prefix: F) -> String
+where
+    F: FnMut(&Line) -> String,
+{
+    let mut rendered = String::new();
+
+    for annotated in annotate(lines, symbols) {
+        let prefix = prefix(annotated.line);
+
+        if let Some(label) = annotated.label {
+            rendered.push_str(&format!("{}{}:\n", " ".repeat(prefix.len()), label));
+        }
+
+        rendered.push_str(&prefix);
+        rendered.push_str("    ");
+        rendered.push_str(&annotated.text);
+
+        if let Some(comment) = annotated.comment {
+            rendered.push_str("  ; ");
+            rendered.push_str(&comment);
+        }
+
+        rendered.push('\n');
+    }
+
+    rendered
+}
+
+fn decode_line(
+    intcode: &[i32],
+    address: usize,
+    instruction_set: &InstructionSet,
+    symbols: &Symbols,
+) -> Option<Line> {
+    let word = intcode[address];
+    let (instruction, modes) = instruction_set.decode(word).ok()?;
+    let arity = instruction.parameters.len();
+    let modes = &modes[..arity];
+
+    if !is_canonical(instruction, modes, word) || address + arity >= intcode.len() {
+        return None;
+    }
+
+    if (address..=address + arity).any(|address| symbols.is_data(address)) {
+        return None;
+    }
+
+    let words = intcode[address..=address + arity].to_vec();
+    let operands = instruction
+        .parameters
+        .iter()
+        .zip(modes)
+        .zip(&words[1..])
+        .map(|((parameter, mode), value)| Operand {
+            parameter: *parameter,
+            mode: *mode,
+            value: *value,
+        })
+        .collect();
+
+    Some(Line {
+        address,
+        words,
+        operation: Operation::Instruction {
+            mnemonic: instruction.mnemonic.clone(),
+            operands,
+        },
+    })
+}
+
+/// Rejects words with stray mode digits, so that disassembling and reassembling a
+/// program gives back the same words.
+fn is_canonical(instruction: &Instruction, modes: &[Mode], word: i32) -> bool {
+    instruction.encode(modes) == word
+}
@@ -0,0 +1,263 @@
+use std::{
+    collections::BTreeMap,
+    convert::TryFrom,
+    io::{self, Read, Write},
+};
+use thiserror::Error;
+
+const MAGIC: &[u8; 4] = b"ICIM";
+const VERSION: u8 = 1;
+const WORD_SIZE: u8 = 4;
+const FLAG_SYMBOLS: u8 = 0b0000_0001;
+
+#[derive(Debug, Error)]
+pub enum ImageError {
+    #[error("Failed to read or write image: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("Not an Intcode image")]
+    BadMagic,
+
+    #[error("Unsupported image version: {0}")]
+    UnsupportedVersion(u8),
+
+    #[error("Unsupported word size: {0} bytes")]
+    UnsupportedWordSize(u8),
+
+    #[error("Image ended unexpectedly")]
+    UnexpectedEnd,
+
+    #[error("Varint is too long")]
+    VarintOverflow,
+
+    #[error("Word doesn't fit in {} bytes: {0}", WORD_SIZE)]
+    WordOverflow(u64),
+
+    #[error("Symbol name is not valid UTF-8")]
+    InvalidSymbolName,
+}
+
+/// A program's memory plus an optional table of labels by address.
+///
+/// Layout: the magic `ICIM`, a version byte, a word size byte (in bytes), a flags
+/// byte, then the memory length and every word as LEB128 varints, words zigzag
+/// encoded. If the symbols flag is set, a count follows, then for each symbol its
+/// address, name length and UTF-8 name.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Image {
+    pub memory: Vec<i32>,
+    pub symbols: BTreeMap<usize, String>,
+}
+
+impl Image {
+    pub fn new(memory: Vec<i32>) -> Self {
+        Image {
+            memory,
+            symbols: BTreeMap::new(),
+        }
+    }
+
+    pub fn is_image(bytes: &[u8]) -> bool {
+        bytes.starts_with(MAGIC)
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(MAGIC.len() + 3 + self.memory.len() * 2);
+        let flags = if self.symbols.is_empty() {
+            0
+        } else {
+            FLAG_SYMBOLS
+        };
+
+        bytes.extend_from_slice(MAGIC);
+        bytes.extend_from_slice(&[VERSION, WORD_SIZE, flags]);
+        write_varint(&mut bytes, self.memory.len() as u64);
+
+        for word in &self.memory {
+            write_varint(&mut bytes, zigzag_encode(*word));
+        }
+
+        if !self.symbols.is_empty() {
+            write_varint(&mut bytes, self.symbols.len() as u64);
+
+            for (address, name) in &self.symbols {
+                write_varint(&mut bytes, *address as u64);
+                write_varint(&mut bytes, name.len() as u64);
+                bytes.extend_from_slice(name.as_bytes());
+            }
+        }
+
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ImageError> {
+        if !Image::is_image(bytes) {
+            return Err(ImageError::BadMagic);
+        }
+
+        let mut reader = ByteReader {
+            bytes,
+            position: MAGIC.len(),
+        };
+
+        let version = reader.byte()?;
+
+        if version != VERSION {
+            return Err(ImageError::UnsupportedVersion(version));
+        }
+
+        let word_size = reader.byte()?;
+
+        if word_size != WORD_SIZE {
+            return Err(ImageError::UnsupportedWordSize(word_size));
+        }
+
+        let flags = reader.byte()?;
+        let length = reader.length()?;
+        let mut memory = Vec::with_capacity(length.min(bytes.len()));
+
+        for _ in 0..length {
+            memory.push(zigzag_decode(reader.varint()?)?);
+        }
+
+        let mut symbols = BTreeMap::new();
+
+        if flags & FLAG_SYMBOLS != 0 {
+            for _ in 0..reader.length()? {
+                let address = reader.length()?;
+                let name_length = reader.length()?;
+                let name = String::from_utf8(reader.take(name_length)?.to_vec())
+                    .map_err(|_| ImageError::InvalidSymbolName)?;
+
+                symbols.insert(address, name);
+            }
+        }
+
+        Ok(Image { memory, symbols })
+    }
+
+    pub fn write_to<W: Write>(&self, mut writer: W) -> Result<(), ImageError> {
+        writer.write_all(&self.to_bytes())?;
+
+        Ok(())
+    }
+
+    pub fn read_from<R: Read>(mut reader: R) -> Result<Self, ImageError> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes)?;
+
+        Image::from_bytes(&bytes)
+    }
+}
+
+struct ByteReader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> ByteReader<'a> {
+    fn byte(&mut self) -> Result<u8, ImageError> {
+        let byte = *self
+            .bytes
+            .get(self.position)
+            .ok_or(ImageError::UnexpectedEnd)?;
+        self.position += 1;
+
+        Ok(byte)
+    }
+
+    fn take(&mut self, count: usize) -> Result<&'a [u8], ImageError> {
+        let end = self
+            .position
+            .checked_add(count)
+            .filter(|end| *end <= self.bytes.len())
+            .ok_or(ImageError::UnexpectedEnd)?;
+        let slice = &self.bytes[self.position..end];
+        self.position = end;
+
+        Ok(slice)
+    }
+
+    fn varint(&mut self) -> Result<u64, ImageError> {
+        let mut value = 0_u64;
+
+        for shift in (0..64).step_by(7) {
+            let byte = self.byte()?;
+            value |= u64::from(byte & 0x7f) << shift;
+
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+
+        Err(ImageError::VarintOverflow)
+    }
+
+    fn length(&mut self) -> Result<usize, ImageError> {
+        usize::try_from(self.varint()?).map_err(|_| ImageError::VarintOverflow)
+    }
+}
+
+fn write_varint(bytes: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        bytes.push((value as u8 & 0x7f) | 0x80);
+        value >>= 7;
+    }
+
+    bytes.push(value as u8);
+}
+
+fn zigzag_encode(word: i32) -> u64 {
+    u64::from(((word << 1) ^ (word >> 31)) as u32)
+}
+
+fn zigzag_decode(value: u64) -> Result<i32, ImageError> {
+    let value = u32::try_from(value).map_err(|_| ImageError::WordOverflow(value))?;
+
+    Ok((value >> 1) as i32 ^ -((value & 1) as i32))
+}
+
+#[cfg(test)]
+mod image_tests {
+    use super::*;
+    use crate::common::intcode;
+    use anyhow::Result;
+    use std::fs;
+
+    #[test]
+    fn round_trip() -> Result<()> {
+        let text = fs::read_to_string("res/day5.txt")?;
+        let mut image = Image::new(intcode::parse_input_to_intcode(&text)?);
+        image.symbols.insert(0, "start".to_string());
+        image.symbols.insert(225, "scratch".to_string());
+        image.memory.extend(&[i32::MIN, i32::MAX, -1]);
+
+        let bytes = image.to_bytes();
+
+        assert!(bytes.len() < text.len());
+        assert_eq!(Image::from_bytes(&bytes)?, image);
+
+        Ok(())
+    }
+
+    #[test]
+    fn rejects_oversized_words() {
+        let mut bytes = Image::new(Vec::new()).to_bytes();
+        bytes.pop();
+        bytes.push(1);
+        write_varint(&mut bytes, u64::from(u32::MAX) + 1);
+
+        assert!(matches!(
+            Image::from_bytes(&bytes),
+            Err(ImageError::WordOverflow(_))
+        ));
+    }
+
+    #[test]
+    fn rejects_text() {
+        assert!(matches!(
+            Image::from_bytes(b"1,9,10,3,2,3,11,0,99,30,40,50"),
+            Err(ImageError::BadMagic)
+        ));
+    }
+}
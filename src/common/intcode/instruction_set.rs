@@ -0,0 +1,248 @@
+use super::{IntcodeError, Machine, Mode};
+use std::{convert::TryFrom, sync::OnceLock};
+
+pub const OPCODE_ADD: i32 = 1;
+pub const OPCODE_MULTIPLY: i32 = 2;
+pub const OPCODE_INPUT: i32 = 3;
+pub const OPCODE_OUTPUT: i32 = 4;
+pub const OPCODE_JUMP_IF_TRUE: i32 = 5;
+pub const OPCODE_JUMP_IF_FALSE: i32 = 6;
+pub const OPCODE_LESS_THAN: i32 = 7;
+pub const OPCODE_EQUALS: i32 = 8;
+pub const OPCODE_HALT: i32 = 99;
+
+/// An i32 instruction word has room for two opcode digits and eight mode digits.
+pub const MAX_PARAMETERS: usize = 8;
+
+const OPCODE_SLOTS: usize = 100;
+
+/// Receives the resolved parameters of an instruction: values for `Read` and `Jump`
+/// parameters, addresses for `Write` parameters.
+pub type Handler = fn(&mut Machine<'_>, &[i32]) -> Result<Action, IntcodeError>;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Parameter {
+    Read,
+    Write,
+    Jump,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Action {
+    Next,
+    Jump(i32),
+    AwaitInput,
+    Halt,
+}
+
+#[derive(Clone)]
+pub struct Instruction {
+    pub opcode: i32,
+    pub mnemonic: String,
+    pub parameters: Vec<Parameter>,
+    pub handler: Handler,
+}
+
+#[derive(Clone)]
+pub struct InstructionSet {
+    instructions: Vec<Option<Instruction>>,
+}
+
+impl Instruction {
+    pub fn new(opcode: i32, mnemonic: &str, parameters: &[Parameter], handler: Handler) -> Self {
+        Instruction {
+            opcode,
+            mnemonic: mnemonic.to_string(),
+            parameters: parameters.to_vec(),
+            handler,
+        }
+    }
+
+    /// Builds the instruction word for this opcode with the given parameter modes.
+    pub fn encode(&self, modes: &[Mode]) -> i32 {
+        modes
+            .iter()
+            .enumerate()
+            .map(|(i, mode)| i32::from(*mode) * 10_i32.pow(i as u32 + 2))
+            .sum::<i32>()
+            + self.opcode
+    }
+
+    /// The modes of this instruction's parameters in an instruction word.
+    pub fn decode_modes(&self, word: i32) -> Result<[Mode; MAX_PARAMETERS], IntcodeError> {
+        let mut modes = [Mode::Position; MAX_PARAMETERS];
+        let mut mode_digits = word / 100;
+
+        for mode in modes.iter_mut().take(self.parameters.len()) {
+            *mode = Mode::try_from(mode_digits % 10)?;
+            mode_digits /= 10;
+        }
+
+        Ok(modes)
+    }
+}
+
+impl Default for InstructionSet {
+    fn default() -> Self {
+        InstructionSet {
+            instructions: vec![None; OPCODE_SLOTS],
+        }
+    }
+}
+
+impl InstructionSet {
+    pub fn new() -> Self {
+        InstructionSet::default()
+    }
+
+    /// The instructions known as of Day 5.
+    pub fn standard() -> &'static InstructionSet {
+        static STANDARD: OnceLock<InstructionSet> = OnceLock::new();
+
+        STANDARD.get_or_init(|| {
+            use Parameter::*;
+
+            let mut instruction_set = InstructionSet::new();
+
+            [
+                Instruction::new(OPCODE_ADD, "add", &[Read, Read, Write], run_add),
+                Instruction::new(OPCODE_MULTIPLY, "mul", &[Read, Read, Write], run_mult),
+                Instruction::new(OPCODE_INPUT, "in", &[Write], run_input),
+                Instruction::new(OPCODE_OUTPUT, "out", &[Read], run_output),
+                Instruction::new(OPCODE_JUMP_IF_TRUE, "jt", &[Read, Jump], run_jump_if_true),
+                Instruction::new(OPCODE_JUMP_IF_FALSE, "jf", &[Read, Jump], run_jump_if_false),
+                Instruction::new(OPCODE_LESS_THAN, "lt", &[Read, Read, Write], run_less_than),
+                Instruction::new(OPCODE_EQUALS, "eq", &[Read, Read, Write], run_equals),
+                Instruction::new(OPCODE_HALT, "hlt", &[], run_halt),
+            ]
+            .iter()
+            .cloned()
+            .try_for_each(|instruction| instruction_set.register(instruction))
+            .expect("Standard instruction set is invalid");
+
+            instruction_set
+        })
+    }
+
+    pub fn register(&mut self, instruction: Instruction) -> Result<(), IntcodeError> {
+        let slot = usize::try_from(instruction.opcode)
+            .ok()
+            .filter(|slot| *slot > 0 && *slot < OPCODE_SLOTS)
+            .ok_or(IntcodeError::InvalidOpcode(instruction.opcode))?;
+
+        if self.instructions[slot].is_some() {
+            return Err(IntcodeError::DuplicateOpcode(instruction.opcode));
+        }
+
+        if self.get_by_mnemonic(&instruction.mnemonic).is_some() {
+            return Err(IntcodeError::DuplicateMnemonic(instruction.mnemonic));
+        }
+
+        if instruction.parameters.len() > MAX_PARAMETERS {
+            return Err(IntcodeError::TooManyParameters(
+                instruction.mnemonic,
+                instruction.parameters.len(),
+            ));
+        }
+
+        self.instructions[slot] = Some(instruction);
+
+        Ok(())
+    }
+
+    pub fn get(&self, opcode: i32) -> Option<&Instruction> {
+        usize::try_from(opcode)
+            .ok()
+            .and_then(|slot| self.instructions.get(slot))
+            .and_then(|instruction| instruction.as_ref())
+    }
+
+    pub fn get_by_mnemonic(&self, mnemonic: &str) -> Option<&Instruction> {
+        self.iter()
+            .find(|instruction| instruction.mnemonic == mnemonic)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Instruction> {
+        self.instructions.iter().flatten()
+    }
+
+    /// Splits an instruction word into its instruction and the modes of its parameters.
+    pub fn decode(
+        &self,
+        word: i32,
+    ) -> Result<(&Instruction, [Mode; MAX_PARAMETERS]), IntcodeError> {
+        if word < 0 {
+            return Err(IntcodeError::NegativeInstruction);
+        }
+
+        let opcode = word % 100;
+        let instruction = self
+            .get(opcode)
+            .ok_or(IntcodeError::UnknownOpcode(opcode))?;
+
+        Ok((instruction, instruction.decode_modes(word)?))
+    }
+}
+
+fn run_add(machine: &mut Machine, params: &[i32]) -> Result<Action, IntcodeError> {
+    machine.write(params[2], params[0] + params[1])?;
+
+    Ok(Action::Next)
+}
+
+fn run_mult(machine: &mut Machine, params: &[i32]) -> Result<Action, IntcodeError> {
+    machine.write(params[2], params[0] * params[1])?;
+
+    Ok(Action::Next)
+}
+
+fn run_input(machine: &mut Machine, params: &[i32]) -> Result<Action, IntcodeError> {
+    match machine.pop_input() {
+        Some(input) => {
+            machine.write(params[0], input)?;
+
+            Ok(Action::Next)
+        }
+        None => Ok(Action::AwaitInput),
+    }
+}
+
+fn run_output(machine: &mut Machine, params: &[i32]) -> Result<Action, IntcodeError> {
+    machine.push_output(params[0]);
+
+    Ok(Action::Next)
+}
+
+fn run_jump_if_true(_: &mut Machine, params: &[i32]) -> Result<Action, IntcodeError> {
+    if params[0] != 0 {
+        Ok(Action::Jump(params[1]))
+    } else {
+        Ok(Action::Next)
+    }
+}
+
+fn run_jump_if_false(_: &mut Machine, params: &[i32]) -> Result<Action, IntcodeError> {
+    if params[0] == 0 {
+        Ok(Action::Jump(params[1]))
+    } else {
+        Ok(Action::Next)
+    }
+}
+
+fn run_less_than(machine: &mut Machine, params: &[i32]) -> Result<Action, IntcodeError> {
+    let output = if params[0] < params[1] { 1 } else { 0 };
+    machine.write(params[2], output)?;
+
+    Ok(Action::Next)
+}
+
+fn run_equals(machine: &mut Machine, params: &[i32]) -> Result<Action, IntcodeError> {
+    let output = if params[0] == params[1] { 1 } else { 0 };
+    machine.write(params[2], output)?;
+
+    Ok(Action::Next)
+}
+
+fn run_halt(_: &mut Machine, _: &[i32]) -> Result<Action, IntcodeError> {
+    Ok(Action::Halt)
+}
@@ -0,0 +1,221 @@
+use super::assembler;
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Display, Formatter},
+    fs, io,
+    ops::RangeInclusive,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+use thiserror::Error;
+
+const SIDECAR_EXTENSION: &str = "sym";
+
+#[derive(Debug, Error)]
+pub enum SymbolsError {
+    #[error("Failed to read or write symbols: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("Line {0}: failed to parse: {1}")]
+    ParseError(usize, String),
+
+    #[error("Line {0}: invalid label {1}")]
+    InvalidLabel(usize, String),
+}
+
+/// Labels, comments and data regions for a program, kept in a sidecar file next to
+/// it (`res/day5.txt` has `res/day5.sym`) so that notes build up over time.
+///
+/// The sidecar has one entry per line, `#` lines are ignored:
+///
+/// ```text
+/// label 225 scratch
+/// comment 6 patched by the instruction at 2
+/// data 223 237
+/// ```
+///
+/// Data regions are inclusive and are never disassembled as instructions.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Symbols {
+    pub labels: BTreeMap<usize, String>,
+    pub comments: BTreeMap<usize, String>,
+    pub data: Vec<RangeInclusive<usize>>,
+}
+
+impl Symbols {
+    pub fn sidecar_path(program: &Path) -> PathBuf {
+        program.with_extension(SIDECAR_EXTENSION)
+    }
+
+    pub fn load(path: &Path) -> Result<Self, SymbolsError> {
+        fs::read_to_string(path)?.parse()
+    }
+
+    /// Loads the program's sidecar, or no symbols if it doesn't have one yet.
+    pub fn load_for_program(program: &Path) -> Result<Self, SymbolsError> {
+        let path = Symbols::sidecar_path(program);
+
+        if path.exists() {
+            Symbols::load(&path)
+        } else {
+            Ok(Symbols::default())
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), SymbolsError> {
+        fs::write(path, self.to_string())?;
+
+        Ok(())
+    }
+
+    pub fn label(&self, address: usize) -> Option<&str> {
+        self.labels.get(&address).map(String::as_str)
+    }
+
+    pub fn comment(&self, address: usize) -> Option<&str> {
+        self.comments.get(&address).map(String::as_str)
+    }
+
+    pub fn address_of(&self, label: &str) -> Option<usize> {
+        self.labels
+            .iter()
+            .find(|(_, name)| *name == label)
+            .map(|(address, _)| *address)
+    }
+
+    pub fn is_data(&self, address: usize) -> bool {
+        self.data.iter().any(|region| region.contains(&address))
+    }
+
+    /// Adds everything from `other`. Its labels win, comments at the same address
+    /// are kept side by side.
+    pub fn merge(&mut self, other: &Symbols) {
+        for (address, label) in &other.labels {
+            self.labels.insert(*address, label.clone());
+        }
+
+        for (address, comment) in &other.comments {
+            self.add_comment(*address, comment);
+        }
+
+        for region in &other.data {
+            if !self.data.contains(region) {
+                self.data.push(region.clone());
+            }
+        }
+    }
+
+    pub fn add_comment(&mut self, address: usize, comment: &str) {
+        self.comments
+            .entry(address)
+            .and_modify(|existing| {
+                if existing != comment {
+                    existing.push_str("; ");
+                    existing.push_str(comment);
+                }
+            })
+            .or_insert_with(|| comment.to_string());
+    }
+}
+
+impl FromStr for Symbols {
+    type Err = SymbolsError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let mut symbols = Symbols::default();
+
+        for (i, line) in input.lines().enumerate() {
+            let line_number = i + 1;
+            let line = line.trim();
+
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let parse_error = || SymbolsError::ParseError(line_number, line.to_string());
+            let mut parts = line.splitn(3, char::is_whitespace);
+            let kind = parts.next().unwrap_or("");
+            let address = parts
+                .next()
+                .and_then(|address| address.parse::<usize>().ok())
+                .ok_or_else(parse_error)?;
+            let rest = parts.next().map(str::trim).unwrap_or("");
+
+            match kind {
+                "label" => {
+                    if !assembler::is_label(rest) {
+                        return Err(SymbolsError::InvalidLabel(line_number, rest.to_string()));
+                    }
+
+                    symbols.labels.insert(address, rest.to_string());
+                }
+                "comment" if !rest.is_empty() => symbols.add_comment(address, rest),
+                "data" => {
+                    let end = rest.parse::<usize>().map_err(|_| parse_error())?;
+                    symbols.data.push(address..=end);
+                }
+                _ => return Err(parse_error()),
+            }
+        }
+
+        Ok(symbols)
+    }
+}
+
+impl Display for Symbols {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let mut entries = Vec::new();
+
+        for region in &self.data {
+            entries.push((
+                *region.start(),
+                0,
+                format!("data {} {}", region.start(), region.end()),
+            ));
+        }
+
+        for (address, label) in &self.labels {
+            entries.push((*address, 1, format!("label {} {}", address, label)));
+        }
+
+        for (address, comment) in &self.comments {
+            entries.push((*address, 2, format!("comment {} {}", address, comment)));
+        }
+
+        entries.sort();
+
+        for (_, _, entry) in entries {
+            writeln!(f, "{}", entry)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod symbols_tests {
+    use super::*;
+    use anyhow::Result;
+
+    #[test]
+    fn round_trip() -> Result<()> {
+        let symbols = Symbols::load_for_program(Path::new("res/day5.txt"))?;
+
+        assert_eq!(symbols.label(238), Some("part2_tests"));
+        assert!(symbols.is_data(230));
+        assert_eq!(symbols.to_string().parse::<Symbols>()?, symbols);
+
+        Ok(())
+    }
+
+    #[test]
+    fn merge_keeps_both_comments() -> Result<()> {
+        let mut symbols = "comment 6 patched at runtime".parse::<Symbols>()?;
+        symbols.merge(&"comment 6 add or jt\nlabel 0 start".parse::<Symbols>()?);
+
+        assert_eq!(symbols.comment(6), Some("patched at runtime; add or jt"));
+        assert_eq!(symbols.address_of("start"), Some(0));
+
+        Ok(())
+    }
+}
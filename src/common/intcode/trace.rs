@@ -0,0 +1,153 @@
+use super::{
+    instruction_set::{Parameter, MAX_PARAMETERS},
+    symbols::Symbols,
+    Access, Mode, Observer, Step,
+};
+use std::io::{self, Write};
+
+/// Writes a line for every instruction executed, using labels and comments from the
+/// symbols, followed by the values of anything it wrote.
+pub struct Tracer<'s, W: Write> {
+    writer: W,
+    symbols: &'s Symbols,
+    error: Option<io::Error>,
+}
+
+impl<'s, W: Write> Tracer<'s, W> {
+    pub fn new(writer: W, symbols: &'s Symbols) -> Self {
+        Tracer {
+            writer,
+            symbols,
+            error: None,
+        }
+    }
+
+    /// Gives back the writer, or the first error hit while writing to it.
+    pub fn finish(self) -> io::Result<W> {
+        match self.error {
+            Some(e) => Err(e),
+            None => Ok(self.writer),
+        }
+    }
+
+    fn format_step(&self, step: &Step) -> String {
+        // The instruction already ran, so its modes decoded fine
+        let modes = step
+            .instruction
+            .decode_modes(step.words[0])
+            .unwrap_or([Mode::Position; MAX_PARAMETERS]);
+
+        let operands = step
+            .instruction
+            .parameters
+            .iter()
+            .zip(&modes)
+            .zip(&step.words[1..])
+            .map(|((parameter, mode), value)| {
+                let label = if *value >= 0 {
+                    self.symbols.label(*value as usize)
+                } else {
+                    None
+                };
+
+                match (mode, parameter, label) {
+                    (Mode::Position, _, Some(label)) => format!("[{}]", label),
+                    (Mode::Position, _, None) => format!("[{}]", value),
+                    (Mode::Immediate, Parameter::Jump, Some(label)) => label.to_string(),
+                    (Mode::Immediate, _, _) => value.to_string(),
+                }
+            })
+            .collect::<Vec<String>>()
+            .join(", ");
+
+        let mut line = format!("{:>5}  ", step.address);
+
+        if let Some(label) = self.symbols.label(step.address) {
+            line.push_str(&format!("{}: ", label));
+        }
+
+        line.push_str(&step.instruction.mnemonic);
+
+        if !operands.is_empty() {
+            line.push(' ');
+            line.push_str(&operands);
+        }
+
+        for access in step.accesses {
+            if let Access::Write(address) = access {
+                let name = match self.symbols.label(*address) {
+                    Some(label) => label.to_string(),
+                    None => address.to_string(),
+                };
+
+                line.push_str(&format!("  {}={}", name, step.memory[*address]));
+            }
+        }
+
+        if let Some(comment) = self.symbols.comment(step.address) {
+            line.push_str(&format!("  ; {}", comment));
+        }
+
+        line
+    }
+}
+
+impl<'s, W: Write> Observer for Tracer<'s, W> {
+    fn on_step(&mut self, step: &Step) {
+        if self.error.is_some() {
+            return;
+        }
+
+        let line = self.format_step(step);
+
+        if let Err(e) = writeln!(self.writer, "{}", line) {
+            self.error = Some(e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod trace_tests {
+    use super::*;
+    use crate::common::intcode::{self, IntcodeError, Machine, State};
+    use anyhow::Result;
+    use std::{fs, path::Path};
+
+    #[test]
+    fn traces_day5_with_symbols() -> Result<()> {
+        let intcode = intcode::parse_input_to_intcode(&fs::read_to_string("res/day5.txt")?)?;
+        let symbols = Symbols::load(Path::new("res/day5.sym"))?;
+        let mut machine = Machine::new(intcode);
+        let mut tracer = Tracer::new(Vec::new(), &symbols);
+        machine.push_input(1);
+
+        assert_eq!(machine.run_with_observer(&mut tracer)?, State::Halted);
+
+        let trace = String::from_utf8(tracer.finish()?)?;
+        let lines = trace.lines().collect::<Vec<&str>>();
+
+        assert_eq!(
+            lines[0],
+            "    0  start: in [scratch]  scratch=1  ; reads the system ID"
+        );
+        assert!(lines.contains(&"  220  report: out [code]  ; outputs the diagnostic code"));
+        assert_eq!(lines.last(), Some(&"  222  hlt"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn jumps_out_of_the_program() -> Result<()> {
+        let symbols = Symbols::default();
+        let mut machine = Machine::new(vec![1105, 1, 100]);
+        let mut tracer = Tracer::new(Vec::new(), &symbols);
+
+        assert!(matches!(
+            machine.run_with_observer(&mut tracer),
+            Err(IntcodeError::UnexpectedEndOfIntcode)
+        ));
+        assert_eq!(String::from_utf8(tracer.finish()?)?, "    0  jt 1, 100\n");
+
+        Ok(())
+    }
+}
@@ -0,0 +1,29 @@
+use serde::{de::DeserializeOwned, Serialize};
+use std::{fs, io, path::Path};
+
+/// Reads a JSON file kept alongside the inputs, or the empty default if there isn't
+/// one yet. Errors convert into the caller's own error type.
+pub fn load<T, E>(path: &Path) -> Result<T, E>
+where
+    T: DeserializeOwned + Default,
+    E: From<io::Error> + From<serde_json::Error>,
+{
+    if !path.exists() {
+        return Ok(T::default());
+    }
+
+    Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+}
+
+/// Writes the value as pretty JSON, ending with a newline so it diffs cleanly.
+pub fn save<T, E>(path: &Path, value: &T) -> Result<(), E>
+where
+    T: Serialize,
+    E: From<io::Error> + From<serde_json::Error>,
+{
+    let mut json = serde_json::to_string_pretty(value)?;
+    json.push('\n');
+    fs::write(path, json)?;
+
+    Ok(())
+}
@@ -0,0 +1,35 @@
+use std::{
+    env, fs, io,
+    path::{Path, PathBuf},
+};
+
+/// A fresh directory for a test under the system's temp directory, deleted along with
+/// everything in it when dropped.
+pub struct TestDir {
+    path: PathBuf,
+}
+
+impl TestDir {
+    /// The name only has to be unique among the tests, runs are told apart by process.
+    pub fn new(name: &str) -> io::Result<Self> {
+        let path = env::temp_dir().join(format!("aoc-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(&path)?;
+
+        Ok(TestDir { path })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn join<P: AsRef<Path>>(&self, path: P) -> PathBuf {
+        self.path.join(path)
+    }
+}
+
+impl Drop for TestDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}
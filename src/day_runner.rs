@@ -1,70 +1,607 @@
-use crate::days::{day1::Day1, day2::Day2, day3::Day3, day4::Day4, day5::Day5, Day};
-use anyhow::{anyhow, Error, Result};
-use std::collections::HashMap;
+pub mod answers;
+pub mod measure;
+pub mod output;
+pub mod perf;
+pub mod watch;
 
-const PUZZLE_DAYS: usize = 25;
+use crate::{
+    days::{answer::Answer, input::InputSource, CommonError, DayEntry, ParsedInput, Solution},
+    years::{self, Year},
+};
+use answers::{Answers, Verdict};
+use anyhow::{anyhow, Result};
+use measure::{MeasureConfig, Measurement};
+use rayon::{prelude::*, ThreadPoolBuilder};
+use std::{
+    fmt::{self, Display, Formatter},
+    sync::{Arc, OnceLock},
+    time::{Duration, Instant},
+};
+use thiserror::Error;
+
+const PARTS: [u32; 2] = [1, 2];
+
+/// Asking the runner for something it can't do, rather than a day failing.
+#[derive(Debug, Error)]
+pub enum RunnerError {
+    #[error("That's... not a valid day: {0}")]
+    InvalidDay(u32),
+
+    #[error("There is no part {0}!")]
+    InvalidPart(u32),
+
+    #[error("I haven't got to day {0} yet!")]
+    NotImplemented(u32),
+
+    #[error("A single input needs a day to run it with")]
+    SingleInputNeedsDay,
+}
 
 pub struct DayRunner {
-    days: HashMap<u32, Box<dyn Day>>,
+    puzzle_days: u32,
+    days: Vec<LazyDay>,
+    input: InputSource,
+    config: MeasureConfig,
+    answers: Answers,
+    jobs: usize,
+}
+
+/// A registered day, constructed from its input the first time it's run. If that
+/// fails, the error is kept and reported for each of its parts.
+struct LazyDay {
+    entry: &'static DayEntry,
+    loaded: OnceLock<Result<LoadedDay, PartError>>,
+}
+
+/// Shared, since a day that fails to load fails both of its parts the same way.
+pub type PartError = Arc<anyhow::Error>;
+
+struct LoadedDay {
+    day: Box<dyn Solution>,
+    parsed: ParsedInput,
+    input: Measurement,
+    parse: Measurement,
+    input_hash: Option<String>,
+}
+
+pub struct PartResult {
+    pub day: u32,
+    pub part: u32,
+    pub answer: Result<Answer, PartError>,
+    /// Reading and parsing the day's input, shared by both of its parts
+    pub input: Measurement,
+    pub parse: Measurement,
+    pub solve: Measurement,
+    pub input_hash: Option<String>,
+    pub verdict: Verdict,
+}
+
+/// Every part that was run, in order, and how long running all of them took.
+pub struct RunSummary {
+    pub parts: Vec<PartResult>,
+    pub total: Duration,
 }
 
 impl DayRunner {
     pub fn new() -> Result<DayRunner> {
-        let mut days = HashMap::<u32, Box<dyn Day>>::with_capacity(PUZZLE_DAYS);
-        days.insert(1, Box::new(Day1::new()?));
-        days.insert(2, Box::new(Day2::new()?));
-        days.insert(3, Box::new(Day3::new()?));
-        days.insert(4, Box::new(Day4::new()?));
-        days.insert(5, Box::new(Day5::new()?));
+        DayRunner::with_measure_config(MeasureConfig::default())
+    }
+
+    pub fn with_measure_config(config: MeasureConfig) -> Result<DayRunner> {
+        DayRunner::with_input(config, InputSource::from_env(), None)
+    }
 
-        Ok(DayRunner { days })
+    /// Runs every day with input from the source, or only `only_day`. A source holding
+    /// a single input, like a file or stdin, needs the day it's for. Days aren't
+    /// loaded until they're run, so a day with a broken input doesn't stop the others.
+    pub fn with_input(
+        config: MeasureConfig,
+        input: InputSource,
+        only_day: Option<u32>,
+    ) -> Result<DayRunner> {
+        DayRunner::with_year(years::default(), config, input, only_day)
+    }
+
+    /// Like `with_input`, for one year's days.
+    pub fn with_year(
+        year: &'static Year,
+        config: MeasureConfig,
+        input: InputSource,
+        only_day: Option<u32>,
+    ) -> Result<DayRunner> {
+        let input = match only_day {
+            Some(day) => input.buffered(day)?,
+            None if input.is_single() => {
+                return Err(RunnerError::SingleInputNeedsDay.into());
+            }
+            None => input,
+        };
+
+        let days = year
+            .days
+            .iter()
+            .filter(|entry| only_day.is_none_or(|day| day == entry.day))
+            .map(|entry| LazyDay {
+                entry,
+                loaded: OnceLock::new(),
+            })
+            .collect();
+
+        Ok(DayRunner {
+            puzzle_days: year.puzzle_days,
+            days,
+            input,
+            config,
+            answers: Answers::default(),
+            jobs: 0,
+        })
+    }
+
+    /// Answers that results are checked against.
+    pub fn with_answers(mut self, answers: Answers) -> Self {
+        self.answers = answers;
+
+        self
+    }
+
+    /// How many parts to run at once, 0 for one per core, which is the default. The
+    /// allocation counter is shared by every thread, so peak allocation is only
+    /// measured with one job.
+    pub fn with_jobs(mut self, jobs: usize) -> Self {
+        self.jobs = jobs;
+
+        self
+    }
+
+    pub fn puzzle_days(&self) -> u32 {
+        self.puzzle_days
+    }
+
+    pub fn is_registered(&self, day: u32) -> bool {
+        self.days.iter().any(|lazy_day| lazy_day.entry.day == day)
+    }
+
+    /// The day's picture of its input, loading it first if it hasn't been run yet.
+    pub fn visualise(&self, day: u32) -> Result<Option<String>> {
+        let lazy_day = self.lazy_day(day)?;
+
+        match self.load(lazy_day) {
+            Ok(loaded) => Ok(loaded.day.visualise(&loaded.parsed)),
+            Err(e) => Err(anyhow!("{:#}", e)),
+        }
     }
 
     pub fn run_day(&self, day: u32, part: Option<u32>) {
-        if day < 1 || day > 25 {
-            println!("That's... not a valid day");
+        match self.run_selected(day, part) {
+            Ok(summary) => print_day(day, &summary),
+            Err(e) => eprintln!("error: {:#}", e),
+        }
+    }
 
-            return;
+    /// Runs one day, or just one of its parts, collecting the results instead of
+    /// printing them.
+    pub fn run_selected(&self, day: u32, part: Option<u32>) -> Result<RunSummary> {
+        if !(1..=self.puzzle_days).contains(&day) {
+            return Err(RunnerError::InvalidDay(day).into());
+        }
+
+        if let Some(part) = part.filter(|part| !PARTS.contains(part)) {
+            return Err(RunnerError::InvalidPart(part).into());
+        }
+
+        let lazy_day = self.lazy_day(day)?;
+        let start = Instant::now();
+
+        let parts = match part {
+            Some(part) => self.run_parts(&[(lazy_day, part)]),
+            None => self.run_parts(&PARTS.map(|part| (lazy_day, part))),
+        };
+
+        Ok(RunSummary {
+            parts,
+            total: start.elapsed(),
+        })
+    }
+
+    pub fn run_all(&self) -> RunSummary {
+        let start = Instant::now();
+        let parts = self
+            .days
+            .iter()
+            .flat_map(|lazy_day| PARTS.map(|part| (lazy_day, part)))
+            .collect::<Vec<(&LazyDay, u32)>>();
+        let parts = self.run_parts(&parts);
+
+        RunSummary {
+            parts,
+            total: start.elapsed(),
         }
+    }
+
+    fn lazy_day(&self, day: u32) -> Result<&LazyDay, RunnerError> {
+        self.days
+            .iter()
+            .find(|lazy_day| lazy_day.entry.day == day)
+            .ok_or(RunnerError::NotImplemented(day))
+    }
+
+    fn load<'d>(&self, lazy_day: &'d LazyDay) -> &'d Result<LoadedDay, PartError> {
+        lazy_day.loaded.get_or_init(|| {
+            let day = lazy_day.entry.day;
+            let mut loaded = load_day(self.config, || (lazy_day.entry.construct)(&self.input))
+                .map_err(Arc::new)?;
+            self.discard_shared_peak(&mut loaded.input);
+            self.discard_shared_peak(&mut loaded.parse);
+            loaded.input_hash = self
+                .input
+                .read(day)
+                .ok()
+                .map(|input| answers::hash_input(&input));
+
+            Ok(loaded)
+        })
+    }
+
+    /// Runs the parts on a thread pool, unless there's only one job. Results are in the
+    /// same order as the parts either way.
+    fn run_parts(&self, parts: &[(&LazyDay, u32)]) -> Vec<PartResult> {
+        let run_sequentially = || {
+            parts
+                .iter()
+                .map(|&(lazy_day, part)| self.run_lazy_part(lazy_day, part))
+                .collect()
+        };
+
+        if self.jobs == 1 {
+            return run_sequentially();
+        }
+
+        match ThreadPoolBuilder::new().num_threads(self.jobs).build() {
+            Ok(pool) => pool.install(|| {
+                parts
+                    .par_iter()
+                    .map(|&(lazy_day, part)| self.run_lazy_part(lazy_day, part))
+                    .collect()
+            }),
+            Err(_) => run_sequentially(),
+        }
+    }
+
+    fn run_lazy_part(&self, lazy_day: &LazyDay, part: u32) -> PartResult {
+        let day = lazy_day.entry.day;
+
+        match self.load(lazy_day) {
+            Ok(loaded) => self.run_part(day, part, loaded),
+            Err(e) => PartResult {
+                day,
+                part,
+                answer: Err(Arc::clone(e)),
+                input: Measurement::default(),
+                parse: Measurement::default(),
+                solve: Measurement::default(),
+                input_hash: None,
+                verdict: Verdict::Unknown,
+            },
+        }
+    }
+
+    /// With more than one job, other threads' allocations are in the peak too.
+    fn discard_shared_peak(&self, measurement: &mut Measurement) {
+        if self.jobs != 1 {
+            measurement.peak_bytes = None;
+        }
+    }
 
-        match self.days.get(&day) {
-            Some(day_instance) => {
-                if let Some(part) = part {
-                    run_day_part_internal(day, part, &day_instance);
-                } else {
-                    run_day_internal(day, &day_instance)
-                }
+    fn run_part(&self, day_number: u32, day_part: u32, loaded: &LoadedDay) -> PartResult {
+        let (answer, mut solve) = measure::measure(self.config, || {
+            loaded.day.run_part(day_part, &loaded.parsed)
+        });
+        let answer = answer.map_err(Arc::new);
+        self.discard_shared_peak(&mut solve);
+
+        let verdict = match &answer {
+            Ok(answer) => {
+                self.answers
+                    .check(day_number, day_part, loaded.input_hash.as_deref(), answer)
             }
-            None => println!("I haven't got to that day yet!"),
+            Err(_) => Verdict::Unknown,
         };
+
+        PartResult {
+            day: day_number,
+            part: day_part,
+            answer,
+            input: loaded.input,
+            parse: loaded.parse,
+            solve,
+            input_hash: loaded.input_hash.clone(),
+            verdict,
+        }
     }
 }
 
-fn run_day_internal(day_number: u32, day: &Box<dyn Day>) {
-    println!();
-    println!("========== DAY {} ==========", day_number);
-    println!("===== Part 1 =====");
-    print_day_result(day.part1());
-    println!("===== Part 2 =====");
-    print_day_result(day.part2());
+impl PartResult {
+    pub fn is_not_done(&self) -> bool {
+        match &self.answer {
+            Ok(_) => false,
+            Err(e) => matches!(e.downcast_ref(), Some(CommonError::NotDone)),
+        }
+    }
+}
+
+impl RunSummary {
+    /// Whether a part errored, other than by not being done yet.
+    pub fn has_failures(&self) -> bool {
+        self.parts
+            .iter()
+            .any(|part| part.answer.is_err() && !part.is_not_done())
+    }
+
+    pub fn has_wrong_answers(&self) -> bool {
+        self.parts
+            .iter()
+            .any(|part| matches!(part.verdict, Verdict::Wrong { .. }))
+    }
+}
+
+#[cfg(test)]
+impl PartResult {
+    /// A part with the given answer, nothing measured and no verdict.
+    pub(crate) fn sample(day: u32, part: u32, answer: Result<Answer>) -> Self {
+        PartResult {
+            day,
+            part,
+            answer: answer.map_err(Arc::new),
+            input: Measurement::default(),
+            parse: Measurement::default(),
+            solve: Measurement::default(),
+            input_hash: None,
+            verdict: Verdict::Unknown,
+        }
+    }
+}
+
+impl Display for RunSummary {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        const HEADINGS: [&str; 6] = ["Answer", "Check", "Input", "Parse", "Solve", "Peak"];
+
+        let rows = self
+            .parts
+            .iter()
+            .map(|part| {
+                [
+                    match &part.answer {
+                        Ok(answer) => answer.to_string(),
+                        Err(e) => format!("error: {:#}", e),
+                    },
+                    part.verdict.to_string(),
+                    format!("{:.2?}", part.input.mean),
+                    format!("{:.2?}", part.parse.mean),
+                    format!("{:.2?}", part.solve.mean),
+                    format_peak(&part.solve),
+                ]
+            })
+            .collect::<Vec<[String; 6]>>();
+
+        let mut widths = [0; 6];
+
+        for (i, width) in widths.iter_mut().enumerate() {
+            *width = rows
+                .iter()
+                .map(|row| row[i].len())
+                .fold(HEADINGS[i].len(), usize::max);
+        }
+
+        let write_row = |f: &mut Formatter, day: &str, part: &str, row: [&str; 6]| {
+            writeln!(
+                f,
+                "{:>3}  {:>4}  {:<w0$}  {:<w1$}  {:>w2$}  {:>w3$}  {:>w4$}  {:>w5$}",
+                day,
+                part,
+                row[0],
+                row[1],
+                row[2],
+                row[3],
+                row[4],
+                row[5],
+                w0 = widths[0],
+                w1 = widths[1],
+                w2 = widths[2],
+                w3 = widths[3],
+                w4 = widths[4],
+                w5 = widths[5]
+            )
+        };
+
+        write_row(f, "Day", "Part", HEADINGS)?;
+
+        for (part, row) in self.parts.iter().zip(&rows) {
+            write_row(
+                f,
+                &part.day.to_string(),
+                &part.part.to_string(),
+                [&row[0], &row[1], &row[2], &row[3], &row[4], &row[5]],
+            )?;
+        }
+
+        writeln!(f, "Total {:.2?}", self.total)
+    }
 }
 
-fn run_day_part_internal(day_number: u32, day_part: u32, day: &Box<dyn Day>) {
-    let part: Box<dyn Fn() -> Result<String>> = match day_part {
-        1 => Box::new(|| day.part1()),
-        2 => Box::new(|| day.part2()),
-        _ => Box::new(|| Err(anyhow!("There is no part {}!", day_part))),
-    };
+/// Constructs the day from its input, then parses it, timing each separately.
+fn load_day<F>(config: MeasureConfig, load: F) -> Result<LoadedDay>
+where
+    F: FnMut() -> Result<Box<dyn Solution>>,
+{
+    let (day, input) = measure::measure(config, load);
+    let day = day?;
+    let (parsed, parse) = measure::measure(config, || day.parse_input());
+
+    Ok(LoadedDay {
+        parsed: parsed?,
+        day,
+        input,
+        parse,
+        input_hash: None,
+    })
+}
 
+fn format_peak(measurement: &Measurement) -> String {
+    match measurement.peak_bytes {
+        Some(bytes) => measure::format_bytes(bytes),
+        None => "-".to_string(),
+    }
+}
+
+/// Prints a day's results the way the runner always has, a heading for the day and
+/// for each of its parts.
+pub fn print_day(day: u32, summary: &RunSummary) {
     println!();
-    println!("========== DAY {} ==========", day_number);
-    println!("===== Part {} =====", day_part);
-    print_day_result(part());
+    println!("========== DAY {} ==========", day);
+
+    for result in &summary.parts {
+        println!("===== Part {} =====", result.part);
+        print_day_result(result);
+    }
 }
 
-fn print_day_result(result: std::result::Result<String, Error>) {
-    match result {
-        Ok(s) => println!("{}", s),
-        Err(e) => println!("{}", e),
+fn print_day_result(result: &PartResult) {
+    match (&result.answer, &result.verdict) {
+        (Ok(s), Verdict::Unknown) => println!("{}", s),
+        (Ok(s), verdict) => println!("{} ({})", s, verdict),
+        (Err(e), _) => {
+            // There's nothing worth timing in a part that didn't run
+            eprintln!("error: {:#}", e);
+
+            return;
+        }
+    }
+
+    println!(
+        "(input {:.2?}, parse {:.2?}, solve {:.2?}, min {:.2?}, peak {})",
+        result.input.mean,
+        result.parse.mean,
+        result.solve.mean,
+        result.solve.min,
+        format_peak(&result.solve)
+    );
+}
+
+#[cfg(test)]
+mod day_runner_tests {
+    use super::*;
+    use crate::{common::test_dir::TestDir, days::DAYS};
+    use std::{fs, path::Path};
+
+    #[test]
+    fn not_done_is_not_a_failure() {
+        let summary = |parts| RunSummary {
+            parts,
+            total: Duration::default(),
+        };
+
+        assert!(!summary(vec![PartResult::sample(
+            1,
+            1,
+            Err(CommonError::NotDone.into())
+        )])
+        .has_failures());
+        assert!(summary(vec![PartResult::sample(1, 1, Err(anyhow!("broken")))]).has_failures());
+        assert!(summary(vec![PartResult {
+            verdict: Verdict::Wrong {
+                expected: Answer::from(2)
+            },
+            ..PartResult::sample(1, 1, Ok(Answer::from(1)))
+        }])
+        .has_wrong_answers());
+    }
+
+    #[test]
+    fn lines_up_the_table() {
+        let mut solved = PartResult {
+            verdict: Verdict::Correct,
+            ..PartResult::sample(1, 1, Ok(Answer::from(3443395)))
+        };
+        solved.input.mean = Duration::from_millis(1);
+        solved.solve = Measurement {
+            mean: Duration::from_micros(12500),
+            min: Duration::from_millis(12),
+            peak_bytes: Some(2048),
+        };
+
+        let failed = PartResult::sample(3, 2, Err(anyhow!("No input for day 3")));
+
+        let summary = RunSummary {
+            parts: vec![solved, failed],
+            total: Duration::from_millis(20),
+        };
+
+        assert_eq!(
+            summary.to_string(),
+            [
+                "Day  Part  Answer                     Check     Input   Parse    Solve     Peak",
+                "  1     1  3443395                    correct  1.00ms  0.00ns  12.50ms  2.0 KiB",
+                "  3     2  error: No input for day 3  unknown  0.00ns  0.00ns   0.00ns        -",
+                "Total 20.00ms",
+                "",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn missing_input_only_fails_its_day() -> Result<()> {
+        let dir = TestDir::new("runner-missing-input")?;
+
+        for entry in DAYS.iter().filter(|entry| entry.day != 3) {
+            let name = format!("day{}.txt", entry.day);
+            fs::copy(Path::new("res").join(&name), dir.join(&name))?;
+        }
+
+        let source = InputSource::Directory(dir.path().to_path_buf());
+        let summary = DayRunner::with_input(MeasureConfig::default(), source, None)?.run_all();
+        let parts = |day| summary.parts.iter().filter(move |part| part.day == day);
+        let missing = dir.join("day3.txt").display().to_string();
+
+        assert!(parts(1).all(|part| part.answer.is_ok()));
+        assert!(parts(3).all(|part| matches!(
+            &part.answer,
+            Err(e) if e.to_string().contains(&missing)
+                && matches!(e.downcast_ref(), Some(CommonError::InputReadError(3, ..)))
+        )));
+
+        Ok(())
+    }
+
+    #[test]
+    fn keeps_parts_in_order_in_parallel() -> Result<()> {
+        let order = |summary: RunSummary| {
+            summary
+                .parts
+                .iter()
+                .map(|part| (part.day, part.part))
+                .collect::<Vec<(u32, u32)>>()
+        };
+        let sequential = order(DayRunner::new()?.with_jobs(1).run_all());
+        let parallel = DayRunner::new()?.with_jobs(4).run_all();
+
+        assert!(!parallel.has_failures());
+        assert_eq!(order(parallel), sequential);
+        assert_eq!(sequential[..3], [(1, 1), (1, 2), (2, 1)]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn rejects_invalid_days() -> Result<()> {
+        let runner = DayRunner::new()?;
+
+        for (day, part) in [(0, None), (26, None), (1, Some(3))] {
+            let result = runner.run_selected(day, part);
+
+            assert!(matches!(result, Err(e) if e.downcast_ref::<RunnerError>().is_some()));
+        }
+
+        Ok(())
     }
 }
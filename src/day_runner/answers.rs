@@ -0,0 +1,179 @@
+use crate::{common::json_file, days::answer::Answer};
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt::{self, Display, Formatter},
+    io,
+    path::Path,
+};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum AnswersError {
+    #[error("Failed to read or write answers: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("Failed to parse answers: {0}")]
+    ParseError(#[from] serde_json::Error),
+}
+
+/// A known answer for a day's part. Answers without an input hash hold for any input,
+/// answers with one only for the input that hashes to it.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Entry {
+    pub day: u32,
+    pub part: u32,
+    pub answer: Answer,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub input_hash: Option<String>,
+}
+
+/// Answers that have been checked on the Advent of Code site, kept in a JSON file so
+/// the runner can tell when a change breaks a day.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Answers {
+    pub entries: Vec<Entry>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Verdict {
+    Correct,
+    Wrong { expected: Answer },
+    Unknown,
+}
+
+impl Answers {
+    pub fn load(path: &Path) -> Result<Self, AnswersError> {
+        json_file::load(path)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), AnswersError> {
+        json_file::save(path, self)
+    }
+
+    /// The answer for the input with this hash if there is one, otherwise the answer
+    /// for any input.
+    pub fn expected(&self, day: u32, part: u32, input_hash: Option<&str>) -> Option<&Answer> {
+        let for_part = || {
+            self.entries
+                .iter()
+                .filter(move |entry| entry.day == day && entry.part == part)
+        };
+
+        for_part()
+            .find(|entry| input_hash.is_some() && entry.input_hash.as_deref() == input_hash)
+            .or_else(|| for_part().find(|entry| entry.input_hash.is_none()))
+            .map(|entry| &entry.answer)
+    }
+
+    pub fn check(&self, day: u32, part: u32, input_hash: Option<&str>, answer: &Answer) -> Verdict {
+        match self.expected(day, part, input_hash) {
+            Some(expected) if expected == answer => Verdict::Correct,
+            Some(expected) => Verdict::Wrong {
+                expected: expected.clone(),
+            },
+            None => Verdict::Unknown,
+        }
+    }
+
+    /// Adds an answer, replacing any for the same day, part and input hash.
+    pub fn record(&mut self, day: u32, part: u32, input_hash: Option<&str>, answer: &Answer) {
+        let entry = Entry {
+            day,
+            part,
+            answer: answer.clone(),
+            input_hash: input_hash.map(str::to_string),
+        };
+
+        match self.entries.iter_mut().find(|existing| {
+            existing.day == day && existing.part == part && existing.input_hash == entry.input_hash
+        }) {
+            Some(existing) => *existing = entry,
+            None => self.entries.push(entry),
+        }
+
+        self.entries
+            .sort_by(|a, b| (a.day, a.part, &a.input_hash).cmp(&(b.day, b.part, &b.input_hash)));
+    }
+}
+
+impl Verdict {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Verdict::Correct => "correct",
+            Verdict::Wrong { .. } => "wrong",
+            Verdict::Unknown => "unknown",
+        }
+    }
+}
+
+impl Display for Verdict {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Verdict::Wrong { expected } => write!(f, "wrong, expected {}", expected),
+            verdict => write!(f, "{}", verdict.name()),
+        }
+    }
+}
+
+/// 64-bit FNV-1a of the input with surrounding whitespace trimmed, as hex. Only used
+/// to tell inputs apart, so it doesn't need to be cryptographic.
+pub fn hash_input(input: &str) -> String {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    let hash = input.trim().bytes().fold(OFFSET_BASIS, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(PRIME)
+    });
+
+    format!("{:016x}", hash)
+}
+
+#[cfg(test)]
+mod answers_tests {
+    use super::*;
+
+    #[test]
+    fn input_hash_wins() {
+        let mut answers = Answers::default();
+        answers.record(1, 1, None, &Answer::from(100));
+        answers.record(1, 1, Some("abc"), &Answer::from(200));
+
+        assert_eq!(
+            answers.check(1, 1, Some("abc"), &Answer::from(200)),
+            Verdict::Correct
+        );
+        assert_eq!(
+            answers.check(1, 1, Some("def"), &Answer::from(100)),
+            Verdict::Correct
+        );
+        assert_eq!(
+            answers.check(1, 1, None, &Answer::from(200)),
+            Verdict::Wrong {
+                expected: Answer::from(100)
+            }
+        );
+        assert_eq!(
+            answers.check(1, 2, None, &Answer::from(100)),
+            Verdict::Unknown
+        );
+    }
+
+    #[test]
+    fn quoted_numbers_match() -> Result<(), AnswersError> {
+        let answers: Answers =
+            serde_json::from_str(r#"{"entries": [{"day": 1, "part": 1, "answer": "3443395"}]}"#)?;
+
+        assert_eq!(
+            answers.check(1, 1, None, &Answer::from(3443395)),
+            Verdict::Correct
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn hash_ignores_trailing_newline() {
+        assert_eq!(hash_input("1,2,3\n"), hash_input("1,2,3"));
+        assert_ne!(hash_input("1,2,3"), hash_input("1,2,4"));
+    }
+}
@@ -0,0 +1,195 @@
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    sync::atomic::{AtomicBool, AtomicUsize, Ordering},
+    time::{Duration, Instant},
+};
+
+static INSTALLED: AtomicBool = AtomicBool::new(false);
+static CURRENT_BYTES: AtomicUsize = AtomicUsize::new(0);
+static PEAK_BYTES: AtomicUsize = AtomicUsize::new(0);
+
+/// Wraps the system allocator, keeping track of how many bytes are allocated and the
+/// most there have been at once. Install it as the `#[global_allocator]` of a binary
+/// to get peak allocation figures in measurements.
+pub struct CountingAllocator;
+
+#[derive(Clone, Copy, Debug)]
+pub struct MeasureConfig {
+    /// Runs thrown away before measuring
+    pub warmup: u32,
+    /// Runs averaged over, at least one
+    pub repetitions: u32,
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Measurement {
+    pub mean: Duration,
+    pub min: Duration,
+    /// Most bytes allocated at once above what was allocated beforehand, if the
+    /// counting allocator is installed
+    pub peak_bytes: Option<usize>,
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+
+        if !ptr.is_null() {
+            record_alloc(layout.size());
+        }
+
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        CURRENT_BYTES.fetch_sub(layout.size(), Ordering::Relaxed);
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc_zeroed(layout);
+
+        if !ptr.is_null() {
+            record_alloc(layout.size());
+        }
+
+        ptr
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = System.realloc(ptr, layout, new_size);
+
+        if !new_ptr.is_null() {
+            CURRENT_BYTES.fetch_sub(layout.size(), Ordering::Relaxed);
+            record_alloc(new_size);
+        }
+
+        new_ptr
+    }
+}
+
+fn record_alloc(size: usize) {
+    let current = CURRENT_BYTES.fetch_add(size, Ordering::Relaxed) + size;
+    PEAK_BYTES.fetch_max(current, Ordering::Relaxed);
+    INSTALLED.store(true, Ordering::Relaxed);
+}
+
+impl CountingAllocator {
+    pub fn is_installed() -> bool {
+        INSTALLED.load(Ordering::Relaxed)
+    }
+
+    /// Starts a new peak from what's allocated right now, which is returned.
+    fn reset_peak() -> usize {
+        let current = CURRENT_BYTES.load(Ordering::Relaxed);
+        PEAK_BYTES.store(current, Ordering::Relaxed);
+
+        current
+    }
+
+    fn peak() -> usize {
+        PEAK_BYTES.load(Ordering::Relaxed)
+    }
+}
+
+impl Default for MeasureConfig {
+    fn default() -> Self {
+        MeasureConfig {
+            warmup: 0,
+            repetitions: 1,
+        }
+    }
+}
+
+/// Runs `f` as the config says, returning the result of the last run along with
+/// how long runs took and how much they allocated.
+pub fn measure<T, F>(config: MeasureConfig, mut f: F) -> (T, Measurement)
+where
+    F: FnMut() -> T,
+{
+    for _ in 0..config.warmup {
+        f();
+    }
+
+    let repetitions = config.repetitions.max(1);
+    let baseline = CountingAllocator::reset_peak();
+    let mut total = Duration::default();
+    let mut min = Duration::MAX;
+    let mut result = None;
+
+    for _ in 0..repetitions {
+        // Drop the previous result before the next run so it isn't counted in the peak
+        drop(result.take());
+
+        let start = Instant::now();
+        let output = f();
+        let elapsed = start.elapsed();
+
+        result = Some(output);
+        total += elapsed;
+        min = min.min(elapsed);
+    }
+
+    let peak_bytes = if CountingAllocator::is_installed() {
+        Some(CountingAllocator::peak().saturating_sub(baseline))
+    } else {
+        None
+    };
+
+    let measurement = Measurement {
+        mean: total / repetitions,
+        min,
+        peak_bytes,
+    };
+
+    // Repetitions is at least one
+    (result.unwrap(), measurement)
+}
+
+pub fn format_bytes(bytes: usize) -> String {
+    const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+
+    let mut size = bytes as f64;
+    let mut unit = 0;
+
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+
+    if unit == 0 {
+        format!("{} {}", bytes, UNITS[0])
+    } else {
+        format!("{:.1} {}", size, UNITS[unit])
+    }
+}
+
+#[cfg(test)]
+mod measure_tests {
+    use super::*;
+
+    #[test]
+    fn warmup_and_repetitions() {
+        let mut runs = 0;
+        let config = MeasureConfig {
+            warmup: 2,
+            repetitions: 3,
+        };
+
+        let (result, measurement) = measure(config, || {
+            runs += 1;
+            runs
+        });
+
+        assert_eq!(runs, 5);
+        assert_eq!(result, 5);
+        assert!(measurement.min <= measurement.mean);
+    }
+
+    #[test]
+    fn bytes() {
+        assert_eq!(format_bytes(512), "512 B");
+        assert_eq!(format_bytes(1536), "1.5 KiB");
+        assert_eq!(format_bytes(3 * 1024 * 1024), "3.0 MiB");
+    }
+}
@@ -0,0 +1,213 @@
+use super::{answers::Verdict, PartResult, RunSummary};
+use crate::days::answer::Answer;
+use serde::Serialize;
+use std::str::FromStr;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum OutputError {
+    #[error("Unknown output format: {0}, expected text, json, csv or markdown")]
+    UnknownFormat(String),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OutputFormat {
+    Text,
+    Json,
+    Csv,
+    Markdown,
+}
+
+/// One part of a run in a shape that's easy for scripts to consume.
+#[derive(Debug, Serialize)]
+pub struct Record {
+    pub day: u32,
+    pub part: u32,
+    pub answer: Option<Answer>,
+    pub error: Option<String>,
+    /// correct, wrong or unknown
+    pub status: &'static str,
+    pub expected: Option<Answer>,
+    pub duration_ms: f64,
+    pub input_ms: f64,
+    pub parse_ms: f64,
+    pub peak_bytes: Option<usize>,
+}
+
+#[derive(Debug, Serialize)]
+struct Report {
+    parts: Vec<Record>,
+    total_ms: f64,
+}
+
+impl FromStr for OutputFormat {
+    type Err = OutputError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        match input.to_lowercase().as_str() {
+            "text" => Ok(OutputFormat::Text),
+            "json" => Ok(OutputFormat::Json),
+            "csv" => Ok(OutputFormat::Csv),
+            "markdown" | "md" => Ok(OutputFormat::Markdown),
+            _ => Err(OutputError::UnknownFormat(input.to_string())),
+        }
+    }
+}
+
+impl From<&PartResult> for Record {
+    fn from(result: &PartResult) -> Self {
+        let (answer, error) = match &result.answer {
+            Ok(answer) => (Some(answer.clone()), None),
+            Err(e) => (None, Some(format!("{:#}", e))),
+        };
+
+        let expected = match &result.verdict {
+            Verdict::Wrong { expected } => Some(expected.clone()),
+            _ => None,
+        };
+
+        Record {
+            day: result.day,
+            part: result.part,
+            answer,
+            error,
+            status: result.verdict.name(),
+            expected,
+            duration_ms: result.solve.mean.as_secs_f64() * 1000.0,
+            input_ms: result.input.mean.as_secs_f64() * 1000.0,
+            parse_ms: result.parse.mean.as_secs_f64() * 1000.0,
+            peak_bytes: result.solve.peak_bytes,
+        }
+    }
+}
+
+pub fn render(summary: &RunSummary, format: OutputFormat) -> String {
+    let records = summary
+        .parts
+        .iter()
+        .map(Record::from)
+        .collect::<Vec<Record>>();
+
+    match format {
+        OutputFormat::Text => summary.to_string(),
+        OutputFormat::Json => {
+            let report = Report {
+                parts: records,
+                total_ms: summary.total.as_secs_f64() * 1000.0,
+            };
+
+            // Serialising plain structs of strings and numbers can't fail
+            let mut json = serde_json::to_string_pretty(&report).unwrap();
+            json.push('\n');
+
+            json
+        }
+        OutputFormat::Csv => {
+            let mut csv = String::from(
+                "day,part,answer,error,status,expected,duration_ms,input_ms,parse_ms,peak_bytes\n",
+            );
+
+            for record in records {
+                csv.push_str(&format!(
+                    "{},{},{},{},{},{},{:.6},{:.6},{:.6},{}\n",
+                    record.day,
+                    record.part,
+                    escape_csv(&optional(&record.answer)),
+                    escape_csv(record.error.as_deref().unwrap_or("")),
+                    record.status,
+                    escape_csv(&optional(&record.expected)),
+                    record.duration_ms,
+                    record.input_ms,
+                    record.parse_ms,
+                    record.peak_bytes.map(|b| b.to_string()).unwrap_or_default()
+                ));
+            }
+
+            csv
+        }
+        OutputFormat::Markdown => {
+            let mut markdown = String::from(
+                "| Day | Part | Answer | Error | Check | Duration (ms) |\n|---:|---:|---|---|---|---:|\n",
+            );
+
+            for record in records {
+                markdown.push_str(&format!(
+                    "| {} | {} | {} | {} | {} | {:.3} |\n",
+                    record.day,
+                    record.part,
+                    escape_markdown(&optional(&record.answer)),
+                    escape_markdown(record.error.as_deref().unwrap_or("")),
+                    record.status,
+                    record.duration_ms
+                ));
+            }
+
+            markdown.push_str(&format!(
+                "\nTotal: {:.3} ms\n",
+                summary.total.as_secs_f64() * 1000.0
+            ));
+
+            markdown
+        }
+    }
+}
+
+fn optional(answer: &Option<Answer>) -> String {
+    answer.as_ref().map(Answer::to_string).unwrap_or_default()
+}
+
+fn escape_csv(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+fn escape_markdown(cell: &str) -> String {
+    cell.replace('|', "\\|").replace('\n', "<br>")
+}
+
+#[cfg(test)]
+mod output_tests {
+    use super::*;
+    use anyhow::anyhow;
+    use std::time::Duration;
+
+    fn summary() -> RunSummary {
+        RunSummary {
+            parts: vec![
+                PartResult {
+                    verdict: Verdict::Correct,
+                    ..PartResult::sample(1, 1, Ok(Answer::from(3443395)))
+                },
+                PartResult::sample(1, 2, Err(anyhow!("Not done, yet"))),
+            ],
+            total: Duration::from_millis(2),
+        }
+    }
+
+    #[test]
+    fn json() -> anyhow::Result<()> {
+        let json =
+            serde_json::from_str::<serde_json::Value>(&render(&summary(), OutputFormat::Json))?;
+
+        assert_eq!(json["parts"][0]["answer"], 3443395);
+        assert_eq!(json["parts"][0]["status"], "correct");
+        assert_eq!(json["parts"][1]["error"], "Not done, yet");
+        assert_eq!(json["total_ms"], 2.0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn csv() {
+        let csv = render(&summary(), OutputFormat::Csv);
+
+        assert!(csv
+            .lines()
+            .nth(2)
+            .unwrap()
+            .starts_with("1,2,,\"Not done, yet\","));
+    }
+}
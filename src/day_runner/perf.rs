@@ -0,0 +1,293 @@
+use super::{measure::MeasureConfig, RunSummary};
+use crate::common::json_file;
+use serde::{Deserialize, Serialize};
+use std::{
+    io,
+    path::Path,
+    process::Command,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use thiserror::Error;
+
+/// Slowdowns smaller than this are noise, however big they are relatively.
+pub const NOISE_FLOOR: Duration = Duration::from_micros(50);
+
+#[derive(Debug, Error)]
+pub enum PerfError {
+    #[error("Failed to read or write timings: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("Failed to parse timings: {0}")]
+    ParseError(#[from] serde_json::Error),
+
+    #[error("No timings for revision {0}")]
+    UnknownRevision(String),
+
+    #[error("Need timings for two revisions to compare, run on another revision first")]
+    NothingToCompare,
+}
+
+/// How long a part took to solve, in nanoseconds, on which input and measured how.
+/// Timings from before the input and settings were kept have no repetitions, so
+/// they're never compared.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PartTiming {
+    pub day: u32,
+    pub part: u32,
+    pub mean_ns: u64,
+    pub min_ns: u64,
+    #[serde(default)]
+    pub input_hash: Option<String>,
+    #[serde(default)]
+    pub warmup: u32,
+    #[serde(default)]
+    pub repetitions: u32,
+}
+
+/// The latest timings of every part run at one revision.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Revision {
+    pub revision: String,
+    /// Seconds since the Unix epoch of the last run
+    pub at: u64,
+    pub parts: Vec<PartTiming>,
+}
+
+/// Timings kept from every run, one entry per git revision, so a slowdown can be
+/// caught without running the criterion benchmarks.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Timings {
+    pub revisions: Vec<Revision>,
+}
+
+/// A part that's in both revisions being compared.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Comparison {
+    pub day: u32,
+    pub part: u32,
+    pub base: Duration,
+    pub head: Duration,
+    pub slower: bool,
+}
+
+impl Timings {
+    pub fn load(path: &Path) -> Result<Self, PerfError> {
+        json_file::load(path)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), PerfError> {
+        json_file::save(path, self)
+    }
+
+    /// Adds the timings of the parts that were solved, replacing older timings of the
+    /// same parts at this revision. The revision becomes the latest.
+    pub fn record(&mut self, revision: &str, config: MeasureConfig, summary: &RunSummary) {
+        let mut entry = match self.revisions.iter().position(|r| r.revision == revision) {
+            Some(i) => self.revisions.remove(i),
+            None => Revision {
+                revision: revision.to_string(),
+                at: 0,
+                parts: Vec::new(),
+            },
+        };
+
+        for result in summary.parts.iter().filter(|result| result.answer.is_ok()) {
+            let timing = PartTiming {
+                day: result.day,
+                part: result.part,
+                mean_ns: result.solve.mean.as_nanos() as u64,
+                min_ns: result.solve.min.as_nanos() as u64,
+                input_hash: result.input_hash.clone(),
+                warmup: config.warmup,
+                repetitions: config.repetitions,
+            };
+
+            match entry
+                .parts
+                .iter_mut()
+                .find(|part| part.day == timing.day && part.part == timing.part)
+            {
+                Some(part) => *part = timing,
+                None => entry.parts.push(timing),
+            }
+        }
+
+        entry.parts.sort_by_key(|part| (part.day, part.part));
+        entry.at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |since| since.as_secs());
+        self.revisions.push(entry);
+    }
+
+    fn find(&self, revision: &str) -> Result<&Revision, PerfError> {
+        self.revisions
+            .iter()
+            .rfind(|entry| entry.revision.starts_with(revision))
+            .ok_or_else(|| PerfError::UnknownRevision(revision.to_string()))
+    }
+
+    /// Compares the parts run at both revisions on the same input with the same
+    /// settings, by their fastest run since that's the least noisy. Without revisions,
+    /// the latest run is compared to the one before. A part is slower if it took more
+    /// than `threshold` percent longer.
+    pub fn compare(
+        &self,
+        base: Option<&str>,
+        head: Option<&str>,
+        threshold: f64,
+    ) -> Result<(&Revision, &Revision, Vec<Comparison>), PerfError> {
+        let head = match head {
+            Some(head) => self.find(head)?,
+            None => self.revisions.last().ok_or(PerfError::NothingToCompare)?,
+        };
+        let base = match base {
+            Some(base) => self.find(base)?,
+            None => self
+                .revisions
+                .iter()
+                .rfind(|entry| entry.revision != head.revision)
+                .ok_or(PerfError::NothingToCompare)?,
+        };
+
+        let comparisons = head
+            .parts
+            .iter()
+            .filter_map(|head_part| {
+                let base_part = base
+                    .parts
+                    .iter()
+                    .find(|part| part.is_comparable(head_part))?;
+                let base_time = Duration::from_nanos(base_part.min_ns);
+                let head_time = Duration::from_nanos(head_part.min_ns);
+
+                Some(Comparison {
+                    day: head_part.day,
+                    part: head_part.part,
+                    base: base_time,
+                    head: head_time,
+                    slower: head_time > base_time + NOISE_FLOOR
+                        && head_time.as_secs_f64()
+                            > base_time.as_secs_f64() * (1.0 + threshold / 100.0),
+                })
+            })
+            .collect();
+
+        Ok((base, head, comparisons))
+    }
+}
+
+impl PartTiming {
+    fn is_comparable(&self, other: &PartTiming) -> bool {
+        self.day == other.day
+            && self.part == other.part
+            && self.input_hash.is_some()
+            && self.input_hash == other.input_hash
+            && self.repetitions > 0
+            && self.warmup == other.warmup
+            && self.repetitions == other.repetitions
+    }
+}
+
+impl Comparison {
+    /// How much longer the head took than the base, as a percentage.
+    pub fn change(&self) -> f64 {
+        if self.base.is_zero() {
+            return 0.0;
+        }
+
+        (self.head.as_secs_f64() / self.base.as_secs_f64() - 1.0) * 100.0
+    }
+}
+
+/// The checked out git revision, marked dirty if there are uncommitted changes, or
+/// `unknown` outside a git checkout.
+pub fn current_revision() -> String {
+    Command::new("git")
+        .args(["describe", "--always", "--dirty"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .map(|revision| revision.trim().to_string())
+        .filter(|revision| !revision.is_empty())
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+#[cfg(test)]
+mod perf_tests {
+    use super::*;
+    use crate::{
+        day_runner::{measure::Measurement, PartResult},
+        days::answer::Answer,
+    };
+
+    fn summary(times_ms: &[u64]) -> RunSummary {
+        summary_for("input", times_ms)
+    }
+
+    fn summary_for(input: &str, times_ms: &[u64]) -> RunSummary {
+        RunSummary {
+            parts: times_ms
+                .iter()
+                .enumerate()
+                .map(|(i, &ms)| PartResult {
+                    solve: Measurement {
+                        mean: Duration::from_millis(ms),
+                        min: Duration::from_millis(ms),
+                        peak_bytes: None,
+                    },
+                    input_hash: Some(input.to_string()),
+                    ..PartResult::sample(1, i as u32 + 1, Ok(Answer::from(1)))
+                })
+                .collect(),
+            total: Duration::default(),
+        }
+    }
+
+    #[test]
+    fn flags_slower_parts() -> Result<(), PerfError> {
+        let config = MeasureConfig::default();
+        let mut timings = Timings::default();
+        timings.record("abc123", config, &summary(&[10, 10]));
+        timings.record("def456", config, &summary(&[10, 20]));
+
+        let (base, head, comparisons) = timings.compare(None, None, 10.0)?;
+
+        assert_eq!(base.revision, "abc123");
+        assert_eq!(head.revision, "def456");
+        assert!(!comparisons[0].slower);
+        assert!(comparisons[1].slower);
+        assert_eq!(comparisons[1].change(), 100.0);
+
+        // Running again at a revision replaces its timings rather than adding more
+        timings.record("abc123", config, &summary(&[30]));
+        assert_eq!(timings.revisions.len(), 2);
+        assert_eq!(timings.revisions[1].parts.len(), 2);
+        assert!(timings.compare(Some("def"), Some("abc"), 10.0)?.2[0].slower);
+
+        Ok(())
+    }
+
+    #[test]
+    fn only_compares_like_with_like() -> Result<(), PerfError> {
+        let config = MeasureConfig::default();
+        let more_repetitions = MeasureConfig {
+            repetitions: 10,
+            ..config
+        };
+        let mut timings = Timings::default();
+        timings.record("abc123", config, &summary(&[10, 10]));
+        timings.record("def456", config, &summary_for("other input", &[20]));
+        timings.record("def456", more_repetitions, &summary(&[10, 20]));
+
+        assert!(timings.compare(None, None, 10.0)?.2.is_empty());
+
+        timings.record("def456", config, &summary(&[20]));
+        let (_, _, comparisons) = timings.compare(None, None, 10.0)?;
+
+        assert_eq!(comparisons.len(), 1);
+        assert!(comparisons[0].slower);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,147 @@
+use super::{answers::Verdict, PartResult, RunSummary};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+
+/// How often watched files are checked for changes.
+pub const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Files compared by modification time between polls. A file that's created or
+/// removed counts as a change too.
+pub struct Watched {
+    paths: Vec<PathBuf>,
+    modified: Vec<Option<SystemTime>>,
+}
+
+impl Watched {
+    pub fn new(paths: Vec<PathBuf>) -> Self {
+        let modified = paths.iter().map(|path| modified(path)).collect();
+
+        Watched { paths, modified }
+    }
+
+    pub fn paths(&self) -> &[PathBuf] {
+        &self.paths
+    }
+
+    /// Whether any file has changed since the last check.
+    pub fn changed(&mut self) -> bool {
+        let modified = self
+            .paths
+            .iter()
+            .map(|path| modified(path))
+            .collect::<Vec<Option<SystemTime>>>();
+        let changed = modified != self.modified;
+        self.modified = modified;
+
+        changed
+    }
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+/// A line for each part of the run, with how its answer and timing compare to the
+/// same part in the previous run.
+pub fn compare(previous: Option<&RunSummary>, current: &RunSummary) -> Vec<String> {
+    current
+        .parts
+        .iter()
+        .map(|result| {
+            let before = previous.and_then(|previous| {
+                previous
+                    .parts
+                    .iter()
+                    .find(|part| part.day == result.day && part.part == result.part)
+            });
+
+            format!(
+                "Part {}: {}, solve {:.2?}{}",
+                result.part,
+                compare_answer(before, result),
+                result.solve.mean,
+                before.map_or(String::new(), |before| format!(
+                    " (was {:.2?})",
+                    before.solve.mean
+                ))
+            )
+        })
+        .collect()
+}
+
+fn compare_answer(before: Option<&PartResult>, result: &PartResult) -> String {
+    let answer = match &result.answer {
+        Ok(answer) => answer.to_string(),
+        Err(e) => return format!("error: {}", e),
+    };
+    let answer = match result.verdict {
+        Verdict::Unknown => answer,
+        _ => format!("{} ({})", answer, result.verdict),
+    };
+
+    match before.map(|before| &before.answer) {
+        Some(Ok(previous)) if result.answer.as_ref().ok() == Some(previous) => {
+            format!("{}, unchanged", answer)
+        }
+        Some(Ok(previous)) => format!("{}, was {}", answer, previous),
+        Some(Err(_)) => format!("{}, was an error", answer),
+        None => answer,
+    }
+}
+
+#[cfg(test)]
+mod watch_tests {
+    use super::*;
+    use crate::{
+        common::test_dir::TestDir, day_runner::measure::Measurement, days::answer::Answer,
+    };
+    use anyhow::Result;
+
+    fn summary(answer: i64, solve: Duration) -> RunSummary {
+        RunSummary {
+            parts: vec![PartResult {
+                solve: Measurement {
+                    mean: solve,
+                    ..Measurement::default()
+                },
+                ..PartResult::sample(1, 1, Ok(Answer::from(answer)))
+            }],
+            total: solve,
+        }
+    }
+
+    #[test]
+    fn compares_with_previous_run() {
+        let first = summary(1, Duration::from_millis(2));
+        let second = summary(2, Duration::from_millis(1));
+
+        assert_eq!(compare(None, &first), ["Part 1: 1, solve 2.00ms"]);
+        assert_eq!(
+            compare(Some(&first), &second),
+            ["Part 1: 2, was 1, solve 1.00ms (was 2.00ms)"]
+        );
+        assert_eq!(
+            compare(Some(&second), &second),
+            ["Part 1: 2, unchanged, solve 1.00ms (was 1.00ms)"]
+        );
+    }
+
+    #[test]
+    fn notices_new_files() -> Result<()> {
+        let dir = TestDir::new("watch")?;
+        let path = dir.join("day1.txt");
+        let mut watched = Watched::new(vec![path.clone()]);
+
+        assert!(!watched.changed());
+        fs::write(&path, "1")?;
+        assert!(watched.changed());
+        assert!(!watched.changed());
+
+        Ok(())
+    }
+}
@@ -1,22 +1,181 @@
-use anyhow::Result;
+use crate::site::SiteError;
+use anyhow::{anyhow, Result};
+use std::{any::Any, io};
 use thiserror::Error;
 
-pub mod day1;
-pub mod day2;
-pub mod day3;
-pub mod day4;
-pub mod day5;
+pub mod answer;
+pub mod examples;
+pub mod input;
+
+// Built with the tests so that changes to `Day` can't leave `new-day` behind
+#[cfg(test)]
+#[allow(dead_code)]
+#[path = "days/day_template.rs"]
+mod day_template;
+
+use answer::Answer;
+use input::InputSource;
+
+/// Declares each day's module and adds it to `DAYS`, so a day only needs a line here
+/// to be run, benchmarked and checked against its answers.
+macro_rules! days {
+    ($($day:literal => $module:ident::$name:ident,)*) => {
+        $(pub mod $module;)*
+
+        /// Every implemented day, in order.
+        pub const DAYS: &[$crate::days::DayEntry] = &[
+            $($crate::days::DayEntry {
+                day: $day,
+                construct: |input| Ok(Box::new($module::$name::from_source(input)?)),
+            },)*
+        ];
+    };
+}
+
+days! {
+    1 => day1::Day1,
+    2 => day2::Day2,
+    3 => day3::Day3,
+    4 => day4::Day4,
+    5 => day5::Day5,
+}
+
+pub type DayConstructor = fn(&InputSource) -> Result<Box<dyn Solution>>;
+
+pub struct DayEntry {
+    pub day: u32,
+    pub construct: DayConstructor,
+}
 
 #[derive(Debug, Error)]
 pub enum CommonError {
-    #[error("Failed to read input for day {0}")]
-    InputReadError(u32),
+    #[error("Failed to read input for day {0} from {1}: {2}")]
+    InputReadError(u32, String, io::Error),
+
+    #[error("Failed to fetch input for day {0}: {1}")]
+    InputFetchError(u32, SiteError),
 
     #[error("Not done yet!")]
     NotDone,
+
+    #[error("Parsed input is for a different day")]
+    ParsedInputMismatch,
+}
+
+/// A day's solution. The input is parsed once and shared by both parts, which may be
+/// run from several threads at once.
+pub trait Day: Send + Sync {
+    type Parsed: Send + Sync + 'static;
+
+    fn parse(&self) -> Result<Self::Parsed>;
+    fn part1(&self, parsed: &Self::Parsed) -> Result<Answer>;
+    fn part2(&self, parsed: &Self::Parsed) -> Result<Answer>;
+
+    /// A picture of the parsed input, for days that have one worth looking at.
+    fn visualise(&self, _parsed: &Self::Parsed) -> Option<String> {
+        None
+    }
+}
+
+/// Parsed input whose type only its day knows.
+pub type ParsedInput = Box<dyn Any + Send + Sync>;
+
+/// A `Day` with the type of its parsed input hidden, so every day can be kept and run
+/// together.
+pub trait Solution: Send + Sync {
+    fn parse_input(&self) -> Result<ParsedInput>;
+    fn run_part(&self, part: u32, parsed: &ParsedInput) -> Result<Answer>;
+    fn visualise(&self, parsed: &ParsedInput) -> Option<String>;
+
+    /// Parses the input and runs one part with it.
+    fn solve(&self, part: u32) -> Result<Answer> {
+        self.run_part(part, &self.parse_input()?)
+    }
 }
 
-pub trait Day {
-    fn part1(&self) -> Result<String>;
-    fn part2(&self) -> Result<String>;
+impl<D: Day> Solution for D {
+    fn parse_input(&self) -> Result<ParsedInput> {
+        Ok(Box::new(self.parse()?))
+    }
+
+    fn run_part(&self, part: u32, parsed: &ParsedInput) -> Result<Answer> {
+        let parsed = parsed
+            .downcast_ref::<D::Parsed>()
+            .ok_or(CommonError::ParsedInputMismatch)?;
+
+        match part {
+            1 => self.part1(parsed),
+            2 => self.part2(parsed),
+            _ => Err(anyhow!("There is no part {}!", part)),
+        }
+    }
+
+    fn visualise(&self, parsed: &ParsedInput) -> Option<String> {
+        Day::visualise(self, parsed.downcast_ref::<D::Parsed>()?)
+    }
+}
+
+#[cfg(test)]
+mod days_tests {
+    use super::*;
+    use crate::{
+        day_runner::answers::{self, Answers, Verdict},
+        years,
+    };
+    use day_template::Day0;
+
+    fn not_done(error: &anyhow::Error) -> bool {
+        matches!(error.downcast_ref(), Some(CommonError::NotDone))
+    }
+
+    #[test]
+    fn template_is_not_done() -> Result<()> {
+        let placeholder = Day0::from_source(&InputSource::Text(String::new()))?;
+        assert!(not_done(&placeholder.parse().unwrap_err()));
+
+        let day = Day0::from_source(&InputSource::Text("1\n2\n".to_string()))?;
+        let lines = day.parse()?;
+        assert_eq!(lines, ["1", "2"]);
+        assert!(not_done(&day.part1(&lines).unwrap_err()));
+        assert!(not_done(&day.part2(&lines).unwrap_err()));
+
+        Ok(())
+    }
+
+    #[test]
+    fn registered_days_match_answers() -> Result<()> {
+        let source = InputSource::default();
+        let answers = Answers::load(&years::default().answers_path())?;
+
+        for entry in DAYS {
+            let input_hash = answers::hash_input(&source.read(entry.day)?);
+            let day = (entry.construct)(&source)?;
+            let parsed = match day.parse_input() {
+                Ok(parsed) => parsed,
+                // A scaffolded day has nothing to parse until its input is filled in
+                Err(e) if not_done(&e) => continue,
+                Err(e) => return Err(e),
+            };
+
+            // Parts that aren't done yet are skipped, any other error fails the test
+            for part in [1, 2] {
+                let answer = match day.run_part(part, &parsed) {
+                    Ok(answer) => answer,
+                    Err(e) if not_done(&e) => continue,
+                    Err(e) => return Err(e.context(format!("day {} part {}", entry.day, part))),
+                };
+                let verdict = answers.check(entry.day, part, Some(&input_hash), &answer);
+
+                assert!(
+                    !matches!(verdict, Verdict::Wrong { .. }),
+                    "day {} part {}: {}",
+                    entry.day,
+                    part,
+                    verdict
+                );
+            }
+        }
+
+        Ok(())
+    }
 }
@@ -0,0 +1,128 @@
+use serde::{Deserialize, Serialize};
+use std::{
+    convert::TryFrom,
+    fmt::{self, Display, Formatter},
+};
+
+/// A part's answer. Most are numbers, some are words, and a few are letters drawn
+/// on a grid that have to be read off it.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged, from = "StoredAnswer")]
+pub enum Answer {
+    Integer(i64),
+    Text(String),
+    /// Rows of a drawing, top to bottom
+    Grid(Vec<String>),
+}
+
+/// An answer as it's written in a file, where a number may have been quoted.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StoredAnswer {
+    Integer(i64),
+    Text(String),
+    Grid(Vec<String>),
+}
+
+impl Answer {
+    /// Reads an answer written out by hand, as a number if it is one.
+    pub fn parse(text: &str) -> Self {
+        let text = text.trim();
+
+        match text.parse::<i64>() {
+            Ok(integer) => Answer::Integer(integer),
+            Err(_) if text.contains('\n') => {
+                Answer::Grid(text.lines().map(str::to_string).collect())
+            }
+            Err(_) => Answer::Text(text.to_string()),
+        }
+    }
+
+    /// Whether the answer is a single line that can be submitted as it is.
+    pub fn is_submittable(&self) -> bool {
+        !matches!(self, Answer::Grid(_))
+    }
+}
+
+impl Display for Answer {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Answer::Integer(integer) => write!(f, "{}", integer),
+            Answer::Text(text) => write!(f, "{}", text),
+            Answer::Grid(rows) => write!(f, "{}", rows.join("\n")),
+        }
+    }
+}
+
+macro_rules! integer_answer {
+    ($($integer:ty),*) => {
+        $(impl From<$integer> for Answer {
+            fn from(integer: $integer) -> Self {
+                // Too big for an i64 is still an answer, just not one to do sums with
+                i64::try_from(integer)
+                    .map(Answer::Integer)
+                    .unwrap_or_else(|_| Answer::Text(integer.to_string()))
+            }
+        })*
+    };
+}
+
+integer_answer!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+
+impl From<String> for Answer {
+    fn from(text: String) -> Self {
+        Answer::Text(text)
+    }
+}
+
+impl From<&str> for Answer {
+    fn from(text: &str) -> Self {
+        Answer::Text(text.to_string())
+    }
+}
+
+impl From<StoredAnswer> for Answer {
+    fn from(stored: StoredAnswer) -> Self {
+        match stored {
+            StoredAnswer::Integer(integer) => Answer::Integer(integer),
+            StoredAnswer::Text(text) => match text.parse::<i64>() {
+                Ok(integer) => Answer::Integer(integer),
+                Err(_) => Answer::Text(text),
+            },
+            StoredAnswer::Grid(rows) => Answer::Grid(rows),
+        }
+    }
+}
+
+impl From<Vec<String>> for Answer {
+    fn from(rows: Vec<String>) -> Self {
+        Answer::Grid(rows)
+    }
+}
+
+#[cfg(test)]
+mod answer_tests {
+    use super::*;
+    use anyhow::Result;
+
+    #[test]
+    fn parses_and_serialises() -> Result<()> {
+        assert_eq!(Answer::parse("1955\n"), Answer::from(1955));
+        assert_eq!(Answer::parse("ABC"), Answer::from("ABC"));
+        assert_eq!(
+            Answer::parse("#..\n.#."),
+            Answer::from(vec!["#..".to_string(), ".#.".to_string()])
+        );
+        assert_eq!(serde_json::to_string(&Answer::from(7u64))?, "7");
+        assert_eq!(
+            serde_json::from_str::<Answer>("\"ABC\"")?,
+            Answer::from("ABC")
+        );
+        assert_eq!(
+            serde_json::from_str::<Answer>("\"3443395\"")?,
+            Answer::from(3443395)
+        );
+
+        Ok(())
+    }
+}
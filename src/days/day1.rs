@@ -1,6 +1,5 @@
-use crate::days::{CommonError, Day};
+use crate::days::{answer::Answer, input::InputSource, Day};
 use anyhow::Result;
-use std::fs;
 use thiserror::Error;
 
 pub struct Day1 {
@@ -15,33 +14,42 @@ pub enum Day1Error {
 
 impl Day1 {
     pub fn new() -> Result<Self> {
-        let input =
-            fs::read_to_string("res/day1.txt").map_err(|_| CommonError::InputReadError(1))?;
+        Day1::from_source(&InputSource::from_env())
+    }
+
+    pub fn from_source(source: &InputSource) -> Result<Self> {
+        let input = source.read(1)?;
 
         Ok(Day1 { input })
     }
 }
 
 impl Day for Day1 {
-    fn part1(&self) -> Result<String> {
-        let fuel = parse_input_to_masses(&self.input)?
-            .into_iter()
+    type Parsed = Vec<u32>;
+
+    fn parse(&self) -> Result<Self::Parsed> {
+        Ok(parse_input_to_masses(&self.input)?)
+    }
+
+    fn part1(&self, masses: &Self::Parsed) -> Result<Answer> {
+        let fuel = masses
+            .iter()
             .map(|mass| (mass / 3) - 2)
             .sum::<u32>();
 
-        Ok(format!("{}", fuel))
+        Ok(fuel.into())
     }
 
-    fn part2(&self) -> Result<String> {
-        let total_fuel = parse_input_to_masses(&self.input)?
-            .into_iter()
+    fn part2(&self, masses: &Self::Parsed) -> Result<Answer> {
+        let total_fuel = masses
+            .iter()
             .map(|mass| (mass / 3) - 2)
             .map(|fuel| {
                 let mut total_fuel = fuel;
                 let mut mass = fuel;
 
                 loop {
-                    mass = (mass / 3).checked_sub(2).unwrap_or(0);
+                    mass = (mass / 3).saturating_sub(2);
 
                     if mass == 0 {
                         break total_fuel;
@@ -52,7 +60,7 @@ impl Day for Day1 {
             })
             .sum::<u32>();
 
-        Ok(format!("{}", total_fuel))
+        Ok(total_fuel.into())
     }
 }
 
@@ -73,14 +81,18 @@ mod day1_tests {
 
     #[test]
     fn part1_puzzle() -> Result<()> {
-        assert_eq!(Day1::new()?.part1()?, "3443395");
+        let day = Day1::new()?;
+
+        assert_eq!(day.part1(&day.parse()?)?, Answer::from(3443395));
 
         Ok(())
     }
 
     #[test]
     fn part2_puzzle() -> Result<()> {
-        assert_eq!(Day1::new()?.part2()?, "5162216");
+        let day = Day1::new()?;
+
+        assert_eq!(day.part2(&day.parse()?)?, Answer::from(5162216));
 
         Ok(())
     }
@@ -1,9 +1,8 @@
 use crate::{
-    common::intcode,
-    days::{CommonError, Day},
+    common::intcode::{self, disassembler, instruction_set::InstructionSet, symbols::Symbols},
+    days::{answer::Answer, input::InputSource, Day},
 };
 use anyhow::Result;
-use std::fs;
 use thiserror::Error;
 
 pub struct Day2 {
@@ -18,28 +17,36 @@ pub enum Day2Error {
 
 impl Day2 {
     pub fn new() -> Result<Self> {
-        let input =
-            fs::read_to_string("res/day2.txt").map_err(|_| CommonError::InputReadError(2))?;
+        Day2::from_source(&InputSource::from_env())
+    }
+
+    pub fn from_source(source: &InputSource) -> Result<Self> {
+        let input = source.read(2)?;
 
         Ok(Day2 { input })
     }
 }
 
 impl Day for Day2 {
-    fn part1(&self) -> Result<String> {
-        let mut intcode = intcode::parse_input_to_intcode(&self.input)?;
+    type Parsed = Vec<i32>;
+
+    fn parse(&self) -> Result<Self::Parsed> {
+        Ok(intcode::parse_input_to_intcode(&self.input)?)
+    }
+
+    fn part1(&self, initial_intcode: &Self::Parsed) -> Result<Answer> {
+        let mut intcode = initial_intcode.clone();
         intcode[1] = 12;
         intcode[2] = 2;
         intcode::run_intcode_to_halt(&mut intcode, None)?;
 
-        Ok(format!("{}", intcode[0]))
+        Ok(intcode[0].into())
     }
 
-    fn part2(&self) -> Result<String> {
+    fn part2(&self, initial_intcode: &Self::Parsed) -> Result<Answer> {
         const DESIRED_OUTPUT: i32 = 19_690_720;
         const MAX_ALLOWED_ITERATIONS: u32 = 1_000_000;
 
-        let initial_intcode = intcode::parse_input_to_intcode(&self.input)?;
         let mut noun = 0;
         let mut verb = 0;
         let mut iterations = 0;
@@ -70,7 +77,13 @@ impl Day for Day2 {
 
         let result = (100 * noun) + verb;
 
-        Ok(format!("{}", result))
+        Ok(result.into())
+    }
+
+    fn visualise(&self, initial_intcode: &Self::Parsed) -> Option<String> {
+        let lines = disassembler::disassemble(initial_intcode, InstructionSet::standard());
+
+        Some(disassembler::listing(&lines, &Symbols::default()))
     }
 }
 
@@ -80,14 +93,18 @@ mod day2_tests {
 
     #[test]
     fn part1_puzzle() -> Result<()> {
-        assert_eq!(Day2::new()?.part1()?, "2842648");
+        let day = Day2::new()?;
+
+        assert_eq!(day.part1(&day.parse()?)?, Answer::from(2842648));
 
         Ok(())
     }
 
     #[test]
     fn part2_puzzle() -> Result<()> {
-        assert_eq!(Day2::new()?.part2()?, "9074");
+        let day = Day2::new()?;
+
+        assert_eq!(day.part2(&day.parse()?)?, Answer::from(9074));
 
         Ok(())
     }
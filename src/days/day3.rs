@@ -1,9 +1,8 @@
-use crate::days::{CommonError, Day};
+use crate::days::{answer::Answer, input::InputSource, Day};
 use anyhow::Result;
 use std::{
     cmp::{Ord, Ordering},
     collections::HashSet,
-    fs,
     hash::{Hash, Hasher},
     result::Result as StdResult,
     str::FromStr,
@@ -41,7 +40,7 @@ pub enum Direction {
     Right,
 }
 
-#[derive(Clone, Debug, Eq, Ord)]
+#[derive(Clone, Debug, Eq)]
 pub struct Point {
     x: i32,
     y: i32,
@@ -50,16 +49,24 @@ pub struct Point {
 
 impl Day3 {
     pub fn new() -> Result<Self> {
-        let input =
-            fs::read_to_string("res/day3.txt").map_err(|_| CommonError::InputReadError(3))?;
+        Day3::from_source(&InputSource::from_env())
+    }
+
+    pub fn from_source(source: &InputSource) -> Result<Self> {
+        let input = source.read(3)?;
 
         Ok(Day3 { input })
     }
 }
 
 impl Day for Day3 {
-    fn part1(&self) -> Result<String> {
-        let paths = parse_input_to_paths(&self.input)?;
+    type Parsed = Vec<Vec<Instruction>>;
+
+    fn parse(&self) -> Result<Self::Parsed> {
+        Ok(parse_input_to_paths(&self.input)?)
+    }
+
+    fn part1(&self, paths: &Self::Parsed) -> Result<Answer> {
         let wire_1_points = find_wire_points_for_instructions(&paths[0]);
         let wire_2_points = find_wire_points_for_instructions(&paths[1]);
 
@@ -70,25 +77,24 @@ impl Day for Day3 {
 
         let result = closest_point.x.abs() + closest_point.y.abs();
 
-        Ok(format!("{}", result))
+        Ok(result.into())
     }
 
-    fn part2(&self) -> Result<String> {
-        let paths = parse_input_to_paths(&self.input)?;
+    fn part2(&self, paths: &Self::Parsed) -> Result<Answer> {
         let wire_1_points = find_wire_points_for_instructions(&paths[0]);
         let wire_2_points = find_wire_points_for_instructions(&paths[1]);
 
         let result = wire_1_points
             .iter()
             .filter_map(|point| {
-                let wire_2_point = wire_2_points.get(&point)?;
+                let wire_2_point = wire_2_points.get(point)?;
 
                 Some(point.steps_from_origin + wire_2_point.steps_from_origin)
             })
             .min()
             .ok_or(Day3Error::NoIntersectionsForWiresFound)?;
 
-        Ok(format!("{}", result))
+        Ok(result.into())
     }
 }
 
@@ -139,7 +145,14 @@ impl PartialEq for Point {
 
 impl PartialOrd for Point {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        (self.x.abs() + self.y.abs()).partial_cmp(&(other.x.abs() + other.y.abs()))
+        Some(self.cmp(other))
+    }
+}
+
+// By position, the same as equality, so the steps taken to get there don't count
+impl Ord for Point {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.x, self.y).cmp(&(other.x, other.y))
     }
 }
 
@@ -149,7 +162,7 @@ fn parse_input_to_paths(input: &str) -> Result<Vec<Vec<Instruction>>, Day3Error>
         .map(|slice| {
             slice
                 .split(",")
-                .map(|instruction| Instruction::from_str(instruction))
+                .map(Instruction::from_str)
                 .collect::<Result<Vec<Instruction>, Day3Error>>()
         })
         .collect::<Result<Vec<Vec<Instruction>>, Day3Error>>()?;
@@ -161,7 +174,7 @@ fn parse_input_to_paths(input: &str) -> Result<Vec<Vec<Instruction>>, Day3Error>
     }
 }
 
-fn find_wire_points_for_instructions(instructions: &Vec<Instruction>) -> HashSet<Point> {
+fn find_wire_points_for_instructions(instructions: &[Instruction]) -> HashSet<Point> {
     const POINT_SET_SIZE: usize = 160_000;
     let mut points = Vec::with_capacity(POINT_SET_SIZE);
 
@@ -227,22 +240,22 @@ fn get_new_latest_point(point: &Point, instruction: &Instruction) -> Point {
         Direction::Up => Point {
             x: point.x,
             y: (point.y + instruction.distance),
-            steps_from_origin: point.steps_from_origin + instruction.distance.abs() as u32,
+            steps_from_origin: point.steps_from_origin + instruction.distance.unsigned_abs(),
         },
         Direction::Down => Point {
             x: point.x,
             y: (point.y - instruction.distance),
-            steps_from_origin: point.steps_from_origin + instruction.distance.abs() as u32,
+            steps_from_origin: point.steps_from_origin + instruction.distance.unsigned_abs(),
         },
         Direction::Left => Point {
             x: (point.x - instruction.distance),
             y: point.y,
-            steps_from_origin: point.steps_from_origin + instruction.distance.abs() as u32,
+            steps_from_origin: point.steps_from_origin + instruction.distance.unsigned_abs(),
         },
         Direction::Right => Point {
             x: (point.x + instruction.distance),
             y: point.y,
-            steps_from_origin: point.steps_from_origin + instruction.distance.abs() as u32,
+            steps_from_origin: point.steps_from_origin + instruction.distance.unsigned_abs(),
         },
     }
 }
@@ -253,14 +266,18 @@ mod day3_tests {
 
     #[test]
     fn part1_puzzle() -> Result<()> {
-        assert_eq!(Day3::new()?.part1()?, "721");
+        let day = Day3::new()?;
+
+        assert_eq!(day.part1(&day.parse()?)?, Answer::from(721));
 
         Ok(())
     }
 
     #[test]
     fn part2_puzzle() -> Result<()> {
-        assert_eq!(Day3::new()?.part2()?, "7388");
+        let day = Day3::new()?;
+
+        assert_eq!(day.part2(&day.parse()?)?, Answer::from(7388));
 
         Ok(())
     }
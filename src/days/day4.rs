@@ -1,6 +1,6 @@
-use crate::days::{CommonError, Day};
+use crate::days::{answer::Answer, input::InputSource, Day};
 use anyhow::Result;
-use std::{collections::HashMap, fs};
+use std::collections::HashMap;
 use thiserror::Error;
 
 pub struct Day4 {
@@ -18,17 +18,24 @@ pub enum Day4Error {
 
 impl Day4 {
     pub fn new() -> Result<Self> {
-        let input =
-            fs::read_to_string("res/day4.txt").map_err(|_| CommonError::InputReadError(4))?;
+        Day4::from_source(&InputSource::from_env())
+    }
+
+    pub fn from_source(source: &InputSource) -> Result<Self> {
+        let input = source.read(4)?;
 
         Ok(Day4 { input })
     }
 }
 
 impl Day for Day4 {
-    fn part1(&self) -> Result<String> {
-        let (start, end) = parse_input(&self.input)?;
+    type Parsed = (u32, u32);
+
+    fn parse(&self) -> Result<Self::Parsed> {
+        Ok(parse_input(&self.input)?)
+    }
 
+    fn part1(&self, &(start, end): &Self::Parsed) -> Result<Answer> {
         let result = (start..=end)
             .map(|password| {
                 let digits = parse_digits(password);
@@ -63,12 +70,10 @@ impl Day for Day4 {
             })
             .sum::<u32>();
 
-        Ok(format!("{}", result))
+        Ok(result.into())
     }
 
-    fn part2(&self) -> Result<String> {
-        let (start, end) = parse_input(&self.input)?;
-
+    fn part2(&self, &(start, end): &Self::Parsed) -> Result<Answer> {
         let result = (start..=end)
             .map(|password| {
                 let digits = parse_digits(password);
@@ -106,7 +111,7 @@ impl Day for Day4 {
             })
             .sum::<u32>();
 
-        Ok(format!("{}", result))
+        Ok(result.into())
     }
 }
 
@@ -140,14 +145,18 @@ mod day4_tests {
 
     #[test]
     fn part1_puzzle() -> Result<()> {
-        assert_eq!(Day4::new()?.part1()?, "1955");
+        let day = Day4::new()?;
+
+        assert_eq!(day.part1(&day.parse()?)?, Answer::from(1955));
 
         Ok(())
     }
 
     #[test]
     fn part2_puzzle() -> Result<()> {
-        assert_eq!(Day4::new()?.part2()?, "1319");
+        let day = Day4::new()?;
+
+        assert_eq!(day.part2(&day.parse()?)?, Answer::from(1319));
 
         Ok(())
     }
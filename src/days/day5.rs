@@ -1,9 +1,8 @@
 use crate::{
-    common::intcode,
-    days::{CommonError, Day},
+    common::intcode::{self, disassembler, instruction_set::InstructionSet, symbols::Symbols},
+    days::{answer::Answer, input::InputSource, Day},
 };
 use anyhow::Result;
-use std::fs;
 use thiserror::Error;
 
 pub struct Day5 {
@@ -18,38 +17,53 @@ pub enum Day5Error {
 
 impl Day5 {
     pub fn new() -> Result<Self> {
-        let input =
-            fs::read_to_string("res/day5.txt").map_err(|_| CommonError::InputReadError(5))?;
+        Day5::from_source(&InputSource::from_env())
+    }
+
+    pub fn from_source(source: &InputSource) -> Result<Self> {
+        let input = source.read(5)?;
 
         Ok(Day5 { input })
     }
 }
 
 impl Day for Day5 {
-    fn part1(&self) -> Result<String> {
+    type Parsed = Vec<i32>;
+
+    fn parse(&self) -> Result<Self::Parsed> {
+        Ok(intcode::parse_input_to_intcode(&self.input)?)
+    }
+
+    fn part1(&self, initial_intcode: &Self::Parsed) -> Result<Answer> {
         const INPUT: Option<i32> = Some(1);
 
-        let mut intcode = intcode::parse_input_to_intcode(&self.input)?;
+        let mut intcode = initial_intcode.clone();
         let mut result = intcode::run_intcode_to_halt(&mut intcode, INPUT)?;
 
         if result.is_empty() {
             return Err(Day5Error::OutputNotFoundError.into());
         };
 
-        Ok(format!("{}", result.pop().unwrap()))
+        Ok(result.pop().unwrap().into())
     }
 
-    fn part2(&self) -> Result<String> {
+    fn part2(&self, initial_intcode: &Self::Parsed) -> Result<Answer> {
         const INPUT: Option<i32> = Some(5);
 
-        let mut intcode = intcode::parse_input_to_intcode(&self.input)?;
+        let mut intcode = initial_intcode.clone();
         let mut result = intcode::run_intcode_to_halt(&mut intcode, INPUT)?;
 
         if result.is_empty() {
             return Err(Day5Error::OutputNotFoundError.into());
         };
 
-        Ok(format!("{}", result.pop().unwrap()))
+        Ok(result.pop().unwrap().into())
+    }
+
+    fn visualise(&self, initial_intcode: &Self::Parsed) -> Option<String> {
+        let lines = disassembler::disassemble(initial_intcode, InstructionSet::standard());
+
+        Some(disassembler::listing(&lines, &Symbols::default()))
     }
 }
 
@@ -59,14 +73,18 @@ mod day5_tests {
 
     #[test]
     fn part1_puzzle() -> Result<()> {
-        assert_eq!(Day5::new()?.part1()?, "15386262");
+        let day = Day5::new()?;
+
+        assert_eq!(day.part1(&day.parse()?)?, Answer::from(15386262));
 
         Ok(())
     }
 
     #[test]
     fn part2_puzzle() -> Result<()> {
-        assert_eq!(Day5::new()?.part2()?, "10376124");
+        let day = Day5::new()?;
+
+        assert_eq!(day.part2(&day.parse()?)?, Answer::from(10376124));
 
         Ok(())
     }
@@ -1,6 +1,5 @@
-use crate::days::{CommonError, Day};
+use crate::days::{answer::Answer, input::InputSource, CommonError, Day};
 use anyhow::Result;
-use std::fs;
 use thiserror::Error;
 
 pub struct Day0 {
@@ -15,37 +14,61 @@ pub enum Day0Error {
 
 impl Day0 {
     pub fn new() -> Result<Self> {
-        let input =
-            fs::read_to_string("res/day0.txt").map_err(|_| CommonError::InputReadError(0))?;
+        Day0::from_source(&InputSource::from_env())
+    }
+
+    pub fn from_source(source: &InputSource) -> Result<Self> {
+        let input = source.read(0)?;
 
         Ok(Day0 { input })
     }
 }
 
 impl Day for Day0 {
-    fn part1(&self) -> Result<String> {
+    type Parsed = Vec<String>;
+
+    fn parse(&self) -> Result<Self::Parsed> {
+        // Scaffolding leaves the input empty until it's fetched or pasted in
+        if self.input.trim().is_empty() {
+            return Err(CommonError::NotDone.into());
+        }
+
+        Ok(parse_input(&self.input)?)
+    }
+
+    fn part1(&self, _lines: &Self::Parsed) -> Result<Answer> {
         Err(CommonError::NotDone.into())
     }
 
-    fn part2(&self) -> Result<String> {
+    fn part2(&self, _lines: &Self::Parsed) -> Result<Answer> {
         Err(CommonError::NotDone.into())
     }
 }
 
+fn parse_input(input: &str) -> Result<Vec<String>, Day0Error> {
+    Ok(input.lines().map(str::to_string).collect())
+}
+
 #[cfg(test)]
 mod day0_tests {
     use super::*;
 
     #[test]
+    #[ignore = "no answer yet"]
     fn part1_puzzle() -> Result<()> {
-        assert_eq!(Day0::new()?.part1()?, "");
+        let day = Day0::new()?;
+
+        assert_eq!(day.part1(&day.parse()?)?, Answer::from(0));
 
         Ok(())
     }
 
     #[test]
+    #[ignore = "no answer yet"]
     fn part2_puzzle() -> Result<()> {
-        assert_eq!(Day0::new()?.part2()?, "");
+        let day = Day0::new()?;
+
+        assert_eq!(day.part2(&day.parse()?)?, Answer::from(0));
 
         Ok(())
     }
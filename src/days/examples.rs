@@ -0,0 +1,178 @@
+use crate::days::{answer::Answer, input::InputSource, DayEntry};
+use anyhow::Result;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+use thiserror::Error;
+
+const HEADER_PREFIX: &str = "--- part ";
+
+#[derive(Debug, Error)]
+pub enum ExamplesError {
+    #[error("{0} line {1}: expected a header like `--- part 1 = 42`, found: {2}")]
+    ParseError(PathBuf, usize, String),
+
+    #[error("Failed to read {0}: {1}")]
+    ReadError(PathBuf, std::io::Error),
+}
+
+/// A worked example from a puzzle's description.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Example {
+    pub day: u32,
+    pub part: u32,
+    pub input: String,
+    pub expected: Answer,
+    /// Line of the example's header in its file
+    pub line: usize,
+}
+
+pub struct ExampleResult {
+    pub example: Example,
+    pub answer: Result<Answer>,
+}
+
+impl ExampleResult {
+    pub fn passed(&self) -> bool {
+        matches!(&self.answer, Ok(answer) if *answer == self.example.expected)
+    }
+}
+
+pub fn examples_path(dir: &Path, day: u32) -> PathBuf {
+    dir.join(format!("day{}.txt", day))
+}
+
+/// Loads a day's examples from `dayN.txt` in the directory, or none if it doesn't
+/// have any. Each example is a header giving the part and its answer, then the input:
+///
+/// ```text
+/// --- part 1 = 2
+/// 12
+/// --- part 2 = 966
+/// 1969
+/// ```
+pub fn load(dir: &Path, day: u32) -> Result<Vec<Example>, ExamplesError> {
+    let path = examples_path(dir, day);
+
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let text = fs::read_to_string(&path).map_err(|e| ExamplesError::ReadError(path.clone(), e))?;
+
+    parse(&path, day, &text)
+}
+
+pub fn parse(path: &Path, day: u32, text: &str) -> Result<Vec<Example>, ExamplesError> {
+    let mut examples = Vec::<Example>::new();
+
+    for (i, line) in text.lines().enumerate() {
+        let line_number = i + 1;
+
+        if let Some(header) = line.strip_prefix(HEADER_PREFIX) {
+            let parse_error =
+                || ExamplesError::ParseError(path.to_path_buf(), line_number, line.to_string());
+            let mut parts = header.splitn(2, '=');
+            let part = parts
+                .next()
+                .and_then(|part| part.trim().parse::<u32>().ok())
+                .ok_or_else(parse_error)?;
+            let expected = Answer::parse(parts.next().ok_or_else(parse_error)?);
+
+            examples.push(Example {
+                day,
+                part,
+                input: String::new(),
+                expected,
+                line: line_number,
+            });
+        } else if let Some(example) = examples.last_mut() {
+            // No trailing newline, some days don't trim their input
+            if !example.input.is_empty() {
+                example.input.push('\n');
+            }
+
+            example.input.push_str(line);
+        } else if !line.trim().is_empty() {
+            return Err(ExamplesError::ParseError(
+                path.to_path_buf(),
+                line_number,
+                line.to_string(),
+            ));
+        }
+    }
+
+    Ok(examples)
+}
+
+/// Runs the example's part on a day constructed from its input.
+pub fn run(entry: &DayEntry, example: &Example) -> ExampleResult {
+    let source = InputSource::Text(example.input.clone());
+    let answer = (entry.construct)(&source).and_then(|day| day.solve(example.part));
+
+    ExampleResult {
+        example: example.clone(),
+        answer,
+    }
+}
+
+/// Runs every example for the given days.
+pub fn run_all<'e, I>(dir: &Path, entries: I) -> Result<Vec<ExampleResult>, ExamplesError>
+where
+    I: IntoIterator<Item = &'e DayEntry>,
+{
+    let mut results = Vec::new();
+
+    for entry in entries {
+        for example in load(dir, entry.day)? {
+            results.push(run(entry, &example));
+        }
+    }
+
+    Ok(results)
+}
+
+#[cfg(test)]
+mod examples_tests {
+    use super::*;
+    use crate::{days::DAYS, years};
+
+    #[test]
+    fn parses_sections() -> Result<()> {
+        let examples = parse(
+            Path::new("test"),
+            1,
+            "--- part 1 = 2\n12\n--- part 2 = 966\n1969\n",
+        )?;
+
+        assert_eq!(examples.len(), 2);
+        assert_eq!(examples[1].part, 2);
+        assert_eq!(examples[1].expected, Answer::from(966));
+        assert_eq!(examples[1].input, "1969");
+        assert!(parse(Path::new("test"), 1, "12\n").is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn all_examples_pass() -> Result<()> {
+        let results = run_all(&years::default().examples_dir(), DAYS)?;
+
+        assert!(!results.is_empty());
+
+        for result in results {
+            assert!(
+                result.passed(),
+                "day {} part {} example at line {}: expected {}, got {:?}",
+                result.example.day,
+                result.example.part,
+                result.example.line,
+                result.example.expected,
+                result.answer.map_err(|e| e.to_string())
+            );
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,153 @@
+use crate::{
+    days::CommonError,
+    site::{fetch::Fetcher, Site},
+    years::{self, DEFAULT_YEAR},
+};
+use std::{
+    env, fs,
+    io::{self, Read},
+    path::{Path, PathBuf},
+};
+
+/// Environment variable that replaces the default input directory, set to either a
+/// directory of `dayN.txt` files or a single input file.
+pub const INPUT_ENV_VAR: &str = "AOC_INPUT";
+
+/// Where a day's puzzle input comes from.
+#[derive(Clone, Debug, PartialEq)]
+pub enum InputSource {
+    /// One input file, whichever day is run.
+    File(PathBuf),
+    /// A directory holding `dayN.txt` for each day.
+    Directory(PathBuf),
+    Stdin,
+    /// Input that has already been read, e.g. from stdin.
+    Text(String),
+}
+
+impl Default for InputSource {
+    fn default() -> Self {
+        years::default().input_source()
+    }
+}
+
+impl InputSource {
+    /// `-` is stdin, an existing directory is a directory of inputs, anything else
+    /// is a single input file.
+    pub fn parse(arg: &str) -> Self {
+        let path = Path::new(arg);
+
+        if arg == "-" {
+            InputSource::Stdin
+        } else if path.is_dir() {
+            InputSource::Directory(path.to_path_buf())
+        } else {
+            InputSource::File(path.to_path_buf())
+        }
+    }
+
+    /// The source from `AOC_INPUT` if it's set, otherwise the `res` directory.
+    pub fn from_env() -> Self {
+        InputSource::from_env_or(InputSource::default())
+    }
+
+    /// The source from `AOC_INPUT` if it's set, otherwise the given one.
+    pub fn from_env_or(default: InputSource) -> Self {
+        match env::var(INPUT_ENV_VAR) {
+            Ok(arg) if !arg.is_empty() => InputSource::parse(&arg),
+            _ => default,
+        }
+    }
+
+    /// Whether this source only holds the input for a single day.
+    pub fn is_single(&self) -> bool {
+        !matches!(self, InputSource::Directory(_))
+    }
+
+    /// Reads stdin up front so the day's input can be read again, e.g. when
+    /// measuring repeated loads.
+    pub fn buffered(self, day: u32) -> Result<Self, CommonError> {
+        match self {
+            InputSource::Stdin => Ok(InputSource::Text(read_stdin(day)?)),
+            source => Ok(source),
+        }
+    }
+
+    pub fn path_for(&self, day: u32) -> Option<PathBuf> {
+        match self {
+            InputSource::File(path) => Some(path.clone()),
+            InputSource::Directory(dir) => Some(dir.join(format!("day{}.txt", day))),
+            InputSource::Stdin | InputSource::Text(_) => None,
+        }
+    }
+
+    pub fn read(&self, day: u32) -> Result<String, CommonError> {
+        match self {
+            InputSource::Directory(dir) => {
+                let path = self.path_for(day).unwrap_or_default();
+
+                // With a session token, a missing input is fetched into the directory, for
+                // the year it belongs to. An empty one is a placeholder from scaffolding a
+                // new day. The site is only set up for those, as inputs are read again for
+                // every repetition that's measured.
+                let missing = fs::metadata(&path).map_or(true, |metadata| metadata.len() == 0);
+
+                if !missing {
+                    return read_file(day, &path);
+                }
+
+                let year = years::for_input_dir(dir).map_or(DEFAULT_YEAR, |year| year.year);
+
+                match Site::from_env_for_year(year) {
+                    Ok(site) => Fetcher::new(site, dir)
+                        .fetch(day)
+                        .map_err(|e| CommonError::InputFetchError(day, e)),
+                    Err(_) => read_file(day, &path),
+                }
+            }
+            InputSource::File(path) => read_file(day, path),
+            InputSource::Stdin => read_stdin(day),
+            InputSource::Text(input) => Ok(input.clone()),
+        }
+    }
+}
+
+fn read_file(day: u32, path: &Path) -> Result<String, CommonError> {
+    fs::read_to_string(path)
+        .map_err(|e| CommonError::InputReadError(day, path.display().to_string(), e))
+}
+
+fn read_stdin(day: u32) -> Result<String, CommonError> {
+    let mut input = String::new();
+    io::stdin()
+        .read_to_string(&mut input)
+        .map_err(|e| CommonError::InputReadError(day, "stdin".to_string(), e))?;
+
+    Ok(input)
+}
+
+#[cfg(test)]
+mod input_tests {
+    use super::*;
+    use anyhow::Result;
+
+    #[test]
+    fn directory() -> Result<()> {
+        let source = InputSource::parse("res");
+
+        assert_eq!(source, InputSource::Directory(PathBuf::from("res")));
+        assert!(!source.is_single());
+        assert!(source.read(1)?.starts_with("1"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn error_has_path() {
+        let source = InputSource::parse("res/day99.txt");
+        let error = source.read(99).unwrap_err().to_string();
+
+        assert!(source.is_single());
+        assert!(error.contains("res/day99.txt"), "{}", error);
+    }
+}
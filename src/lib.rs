@@ -1,3 +1,7 @@
 pub mod common;
 pub mod day_runner;
 pub mod days;
+pub mod scaffold;
+pub mod site;
+pub mod tui;
+pub mod years;
@@ -0,0 +1,201 @@
+use crate::{days::examples, years::Year};
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+use thiserror::Error;
+
+const TEMPLATE_PATH: &str = "src/days/day_template.rs";
+
+#[derive(Debug, Error)]
+pub enum ScaffoldError {
+    #[error("Day {0} isn't a puzzle day")]
+    InvalidDay(u32),
+
+    #[error("{0} already exists")]
+    AlreadyExists(PathBuf),
+
+    #[error("Couldn't find where to add the day in {0}: expected {1}")]
+    AnchorNotFound(PathBuf, String),
+
+    #[error("Failed to read or write {0}: {1}")]
+    Io(PathBuf, io::Error),
+}
+
+/// Adds a day to the year under `root`: its module from the template, its line in the
+/// year's `days!` registry, and empty files for the puzzle input and worked examples.
+/// Every change is worked out before anything is written, and if a write fails, the
+/// files already written are put back. Returns the files written.
+pub fn new_day(root: &Path, year: &Year, day: u32) -> Result<Vec<PathBuf>, ScaffoldError> {
+    if !(1..=year.puzzle_days).contains(&day) {
+        return Err(ScaffoldError::InvalidDay(day));
+    }
+
+    let module_path = root.join(format!("src/{}/day{}.rs", year.module, day));
+    let input_path = root.join(year.res_dir).join(format!("day{}.txt", day));
+    let examples_path = examples::examples_path(&root.join(year.examples_dir()), day);
+
+    if module_path.exists() {
+        return Err(ScaffoldError::AlreadyExists(module_path));
+    }
+
+    let module = read(&root.join(TEMPLATE_PATH))?
+        .replace("Day0", &format!("Day{}", day))
+        .replace("day0", &format!("day{}", day))
+        .replace(".read(0)", &format!(".read({})", day));
+
+    // Each file's new contents, and what it held before if it already existed
+    let mut edits = vec![(module_path, module, None)];
+
+    let days_path = root.join(format!("src/{}.rs", year.module));
+    let original = read(&days_path)?;
+    let days = insert_lines(
+        &days_path,
+        &original,
+        "=> day",
+        day,
+        &[format!("    {0} => day{0}::Day{0},", day)],
+    )?;
+    edits.push((days_path, days, Some(original)));
+
+    for placeholder in [input_path, examples_path] {
+        if !placeholder.exists() {
+            edits.push((placeholder, String::new(), None));
+        }
+    }
+
+    for (i, (path, contents, _)) in edits.iter().enumerate() {
+        if let Err(e) = write(path, contents) {
+            undo(&edits[..i]);
+
+            return Err(e);
+        }
+    }
+
+    Ok(edits.into_iter().map(|(path, _, _)| path).collect())
+}
+
+fn write(path: &Path, contents: &str) -> Result<(), ScaffoldError> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| ScaffoldError::Io(path.to_path_buf(), e))?;
+    }
+
+    fs::write(path, contents).map_err(|e| ScaffoldError::Io(path.to_path_buf(), e))
+}
+
+/// Removes the files that were created and restores the ones that were changed.
+fn undo(written: &[(PathBuf, String, Option<String>)]) {
+    for (path, _, original) in written.iter().rev() {
+        let _ = match original {
+            Some(original) => fs::write(path, original),
+            None => fs::remove_file(path),
+        };
+    }
+}
+
+fn read(path: &Path) -> Result<String, ScaffoldError> {
+    fs::read_to_string(path).map_err(|e| ScaffoldError::Io(path.to_path_buf(), e))
+}
+
+/// The day number right after `marker` in the line, e.g. 5 for `5 => day5::Day5,`.
+fn day_after(line: &str, marker: &str) -> Option<u32> {
+    let rest = &line[line.find(marker)? + marker.len()..];
+    let digits = rest
+        .chars()
+        .take_while(char::is_ascii_digit)
+        .collect::<String>();
+
+    digits.parse().ok()
+}
+
+/// Inserts `lines` after the last line for an earlier day, or before the first line
+/// for a later one, so that days stay in order.
+fn insert_lines(
+    path: &Path,
+    text: &str,
+    marker: &str,
+    day: u32,
+    lines: &[String],
+) -> Result<String, ScaffoldError> {
+    let mut existing = text.lines().map(str::to_string).collect::<Vec<String>>();
+    let days = existing
+        .iter()
+        .enumerate()
+        .filter_map(|(i, line)| day_after(line.trim_start(), marker).map(|day| (i, day)))
+        .collect::<Vec<(usize, u32)>>();
+
+    let index = match days.iter().rev().find(|(_, existing)| *existing < day) {
+        Some((i, _)) => i + 1,
+        None => days
+            .first()
+            .map(|(i, _)| *i)
+            .ok_or_else(|| ScaffoldError::AnchorNotFound(path.to_path_buf(), marker.to_string()))?,
+    };
+
+    existing.splice(index..index, lines.iter().cloned());
+
+    let mut text = existing.join("\n");
+    text.push('\n');
+
+    Ok(text)
+}
+
+#[cfg(test)]
+mod scaffold_tests {
+    use super::*;
+    use crate::{common::test_dir::TestDir, years};
+    use anyhow::Result;
+
+    const REGISTRY: &str = "days! {\n    1 => day1::Day1,\n    5 => day5::Day5,\n}\n";
+
+    /// A tree with the template and a registry of days 1 and 5, whatever is registered
+    /// in the real one.
+    fn fixture(name: &str) -> Result<TestDir> {
+        let root = TestDir::new(&format!("scaffold-{}", name))?;
+
+        fs::create_dir_all(root.join("src/days"))?;
+        fs::copy(TEMPLATE_PATH, root.join(TEMPLATE_PATH))?;
+        fs::write(root.join("src/days.rs"), REGISTRY)?;
+
+        Ok(root)
+    }
+
+    #[test]
+    fn adds_day() -> Result<()> {
+        let root = fixture("add")?;
+        let year = years::default();
+        let days_path = "src/days.rs";
+
+        new_day(root.path(), year, 7)?;
+
+        let module = fs::read_to_string(root.join("src/days/day7.rs"))?;
+        assert!(module.contains("pub struct Day7"));
+        assert!(module.contains("source.read(7)"));
+        assert!(module.contains("mod day7_tests"));
+
+        let days = fs::read_to_string(root.join(days_path))?;
+        assert!(days.contains("    5 => day5::Day5,\n    7 => day7::Day7,\n}"));
+
+        assert!(root.join("res/day7.txt").exists());
+        assert!(root.join("res/examples/day7.txt").exists());
+        assert!(new_day(root.path(), year, 7).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn puts_back_what_was_written() -> Result<()> {
+        let root = fixture("undo")?;
+
+        // The examples directory can't be created where a file is in the way
+        fs::create_dir_all(root.join("res"))?;
+        fs::write(root.join("res/examples"), "")?;
+
+        assert!(new_day(root.path(), years::default(), 3).is_err());
+        assert!(!root.join("src/days/day3.rs").exists());
+        assert!(!root.join("res/day3.txt").exists());
+        assert_eq!(fs::read_to_string(root.join("src/days.rs"))?, REGISTRY);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,201 @@
+pub mod fetch;
+pub mod submit;
+#[cfg(test)]
+mod test_server;
+
+use crate::years::DEFAULT_YEAR;
+use std::{env, io};
+use submit::Reply;
+use thiserror::Error;
+
+pub const DEFAULT_BASE_URL: &str = "https://adventofcode.com";
+
+/// Environment variable holding the value of the site's `session` cookie, which can
+/// be copied from a logged in browser.
+pub const SESSION_ENV_VAR: &str = "AOC_SESSION";
+
+const USER_AGENT: &str = concat!(
+    "github.com/zoewithabang/aoc-2019-rust ",
+    env!("CARGO_PKG_VERSION")
+);
+
+#[derive(Debug, Error)]
+pub enum SiteError {
+    #[error("No session token, set {} to your session cookie", SESSION_ENV_VAR)]
+    NoSession,
+
+    #[error("Request to {0} failed: {1}")]
+    RequestError(String, String),
+
+    #[error("{0} returned {1}: {2}")]
+    StatusError(String, u16, String),
+
+    #[error("Didn't understand the response from {0}")]
+    UnknownResponse(String),
+
+    #[error("Not submitting, {0}")]
+    SubmissionRefused(String),
+
+    #[error("Failed to read or write a local file: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("Failed to parse submission history: {0}")]
+    HistoryError(#[from] serde_json::Error),
+}
+
+pub struct Response {
+    pub status: u16,
+    pub body: String,
+}
+
+/// Just enough HTTP for talking to the puzzle site, so that tests can swap in
+/// something that doesn't go over the network.
+pub trait HttpClient {
+    fn get(&self, url: &str, headers: &[(&str, &str)]) -> Result<Response, SiteError>;
+    fn post_form(
+        &self,
+        url: &str,
+        headers: &[(&str, &str)],
+        form: &[(&str, &str)],
+    ) -> Result<Response, SiteError>;
+}
+
+pub struct UreqClient {
+    agent: ureq::Agent,
+}
+
+/// The puzzle site for one year, logged in with a session token.
+pub struct Site {
+    client: Box<dyn HttpClient>,
+    base_url: String,
+    session: String,
+    year: u32,
+}
+
+impl UreqClient {
+    pub fn new() -> Self {
+        UreqClient {
+            agent: ureq::AgentBuilder::new().user_agent(USER_AGENT).build(),
+        }
+    }
+
+    fn send(url: &str, result: Result<ureq::Response, ureq::Error>) -> Result<Response, SiteError> {
+        let response = match result {
+            Ok(response) => response,
+            // Error statuses still have a body worth showing
+            Err(ureq::Error::Status(_, response)) => response,
+            Err(e) => return Err(SiteError::RequestError(url.to_string(), e.to_string())),
+        };
+        let status = response.status();
+        let body = response
+            .into_string()
+            .map_err(|e| SiteError::RequestError(url.to_string(), e.to_string()))?;
+
+        Ok(Response { status, body })
+    }
+}
+
+impl Default for UreqClient {
+    fn default() -> Self {
+        UreqClient::new()
+    }
+}
+
+impl HttpClient for UreqClient {
+    fn get(&self, url: &str, headers: &[(&str, &str)]) -> Result<Response, SiteError> {
+        let request = headers
+            .iter()
+            .fold(self.agent.get(url), |request, (name, value)| {
+                request.set(name, value)
+            });
+
+        UreqClient::send(url, request.call())
+    }
+
+    fn post_form(
+        &self,
+        url: &str,
+        headers: &[(&str, &str)],
+        form: &[(&str, &str)],
+    ) -> Result<Response, SiteError> {
+        let request = headers
+            .iter()
+            .fold(self.agent.post(url), |request, (name, value)| {
+                request.set(name, value)
+            });
+
+        UreqClient::send(url, request.send_form(form))
+    }
+}
+
+impl Site {
+    pub fn new(client: Box<dyn HttpClient>, base_url: &str, session: &str, year: u32) -> Self {
+        Site {
+            client,
+            base_url: base_url.trim_end_matches('/').to_string(),
+            session: session.trim().to_string(),
+            year,
+        }
+    }
+
+    /// The real site over HTTPS for the default year, with the session token from
+    /// `AOC_SESSION`.
+    pub fn from_env() -> Result<Self, SiteError> {
+        Site::from_env_for_year(DEFAULT_YEAR)
+    }
+
+    pub fn from_env_for_year(year: u32) -> Result<Self, SiteError> {
+        match env::var(SESSION_ENV_VAR) {
+            Ok(session) if !session.trim().is_empty() => Ok(Site::new(
+                Box::new(UreqClient::new()),
+                DEFAULT_BASE_URL,
+                &session,
+                year,
+            )),
+            _ => Err(SiteError::NoSession),
+        }
+    }
+
+    pub fn input(&self, day: u32) -> Result<String, SiteError> {
+        let url = format!("{}/input", self.day_url(day));
+        let response = self.client.get(&url, &[("Cookie", &self.cookie())])?;
+
+        match response.status {
+            200 => Ok(response.body),
+            404 => Err(SiteError::StatusError(
+                url,
+                404,
+                "that day isn't unlocked yet".to_string(),
+            )),
+            400 | 500 => Err(SiteError::StatusError(
+                url,
+                response.status,
+                "the session token was rejected, it may have expired".to_string(),
+            )),
+            status => Err(SiteError::StatusError(url, status, response.body)),
+        }
+    }
+
+    pub fn submit(&self, day: u32, part: u32, answer: &str) -> Result<Reply, SiteError> {
+        let url = format!("{}/answer", self.day_url(day));
+        let level = part.to_string();
+        let response = self.client.post_form(
+            &url,
+            &[("Cookie", &self.cookie())],
+            &[("level", &level), ("answer", answer)],
+        )?;
+
+        match response.status {
+            200 => Reply::parse(&response.body).ok_or(SiteError::UnknownResponse(url)),
+            status => Err(SiteError::StatusError(url, status, response.body)),
+        }
+    }
+
+    fn day_url(&self, day: u32) -> String {
+        format!("{}/{}/day/{}", self.base_url, self.year, day)
+    }
+
+    fn cookie(&self) -> String {
+        format!("session={}", self.session)
+    }
+}
@@ -0,0 +1,91 @@
+use super::{Site, SiteError};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// Fetches puzzle inputs into a cache directory as `dayN.txt`. An input that's
+/// already cached is never downloaded again, inputs don't change.
+pub struct Fetcher {
+    site: Site,
+    cache_dir: PathBuf,
+}
+
+impl Fetcher {
+    pub fn new(site: Site, cache_dir: &Path) -> Self {
+        Fetcher {
+            site,
+            cache_dir: cache_dir.to_path_buf(),
+        }
+    }
+
+    pub fn cache_path(&self, day: u32) -> PathBuf {
+        self.cache_dir.join(format!("day{}.txt", day))
+    }
+
+    /// Whether the input is in the cache. Empty files don't count, they're placeholders
+    /// for inputs that haven't been fetched yet.
+    pub fn is_cached(&self, day: u32) -> bool {
+        fs::metadata(self.cache_path(day)).is_ok_and(|metadata| metadata.len() > 0)
+    }
+
+    pub fn fetch(&self, day: u32) -> Result<String, SiteError> {
+        let path = self.cache_path(day);
+
+        if self.is_cached(day) {
+            return Ok(fs::read_to_string(path)?);
+        }
+
+        let input = self.site.input(day)?;
+        fs::create_dir_all(&self.cache_dir)?;
+        fs::write(path, &input)?;
+
+        Ok(input)
+    }
+}
+
+#[cfg(test)]
+mod fetch_tests {
+    use super::*;
+    use crate::{
+        common::test_dir::TestDir,
+        site::{test_server::TestServer, UreqClient},
+    };
+    use anyhow::Result;
+
+    fn fetcher(server: &TestServer, dir: &TestDir) -> Fetcher {
+        let site = Site::new(Box::new(UreqClient::new()), &server.url(), "abc", 2019);
+
+        Fetcher::new(site, &dir.join("inputs"))
+    }
+
+    #[test]
+    fn downloads_once() -> Result<()> {
+        let server = TestServer::start(|_| (200, "1,2,3\n".to_string()))?;
+        let dir = TestDir::new("fetch-once")?;
+        let fetcher = fetcher(&server, &dir);
+
+        assert_eq!(fetcher.fetch(2)?, "1,2,3\n");
+        assert_eq!(fetcher.fetch(2)?, "1,2,3\n");
+        assert!(fetcher.is_cached(2));
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 1);
+        assert!(requests[0].starts_with("GET /2019/day/2/input "));
+        assert!(requests[0].contains("session=abc"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn locked_day() -> Result<()> {
+        let server = TestServer::start(|_| (404, "Not Found".to_string()))?;
+        let dir = TestDir::new("fetch-locked")?;
+        let fetcher = fetcher(&server, &dir);
+
+        assert!(fetcher.fetch(25).is_err());
+        assert!(!fetcher.is_cached(25));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,356 @@
+use super::{Site, SiteError};
+use crate::common::json_file;
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt::{self, Display, Formatter},
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// How long to wait after a wrong answer if the page doesn't say.
+const WRONG_ANSWER_WAIT_SECS: u64 = 60;
+
+/// What the site made of a submitted answer.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Outcome {
+    Correct,
+    TooHigh,
+    TooLow,
+    Wrong,
+    RateLimited {
+        wait_secs: u64,
+    },
+    /// The part was already solved, or isn't unlocked yet
+    WrongLevel,
+}
+
+/// The outcome of a submission, and how long the site wants before the next one.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Reply {
+    pub outcome: Outcome,
+    pub wait_secs: Option<u64>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Submission {
+    pub day: u32,
+    pub part: u32,
+    pub answer: String,
+    pub outcome: Outcome,
+    /// Seconds since the Unix epoch
+    pub at: u64,
+}
+
+/// Every answer submitted so far, so wrong answers aren't sent twice, and until when
+/// the site last asked to wait, so it isn't asked again before then.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct History {
+    pub submissions: Vec<Submission>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wait_until: Option<u64>,
+}
+
+pub struct Submitter {
+    site: Site,
+    history: History,
+    history_path: PathBuf,
+}
+
+impl Outcome {
+    /// Reads the outcome out of the page the site responds with.
+    pub fn parse(page: &str) -> Option<Outcome> {
+        if page.contains("That's the right answer") {
+            Some(Outcome::Correct)
+        } else if page.contains("your answer is too high") {
+            Some(Outcome::TooHigh)
+        } else if page.contains("your answer is too low") {
+            Some(Outcome::TooLow)
+        } else if page.contains("That's not the right answer") {
+            Some(Outcome::Wrong)
+        } else if page.contains("You gave an answer too recently") {
+            Some(Outcome::RateLimited {
+                wait_secs: parse_rate_limit(page).unwrap_or(60),
+            })
+        } else if page.contains("You don't seem to be solving the right level") {
+            Some(Outcome::WrongLevel)
+        } else {
+            None
+        }
+    }
+
+    pub fn is_wrong(&self) -> bool {
+        matches!(self, Outcome::TooHigh | Outcome::TooLow | Outcome::Wrong)
+    }
+}
+
+impl Display for Outcome {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Outcome::Correct => write!(f, "That's the right answer!"),
+            Outcome::TooHigh => write!(f, "Wrong, too high"),
+            Outcome::TooLow => write!(f, "Wrong, too low"),
+            Outcome::Wrong => write!(f, "Wrong"),
+            Outcome::RateLimited { wait_secs } => {
+                write!(f, "Answered too recently, wait {}s", wait_secs)
+            }
+            Outcome::WrongLevel => write!(f, "Already solved, or not unlocked yet"),
+        }
+    }
+}
+
+impl Reply {
+    pub fn parse(page: &str) -> Option<Reply> {
+        let outcome = Outcome::parse(page)?;
+        let wait_secs = match outcome {
+            Outcome::RateLimited { wait_secs } => Some(wait_secs),
+            _ if outcome.is_wrong() => {
+                Some(parse_wrong_answer_wait(page).unwrap_or(WRONG_ANSWER_WAIT_SECS))
+            }
+            _ => None,
+        };
+
+        Some(Reply { outcome, wait_secs })
+    }
+}
+
+/// The wait in a rate limited page, e.g. `You have 4m 52s left to wait.`
+fn parse_rate_limit(page: &str) -> Option<u64> {
+    let start = page.find("You have ")? + "You have ".len();
+    let end = start + page[start..].find(" left to wait")?;
+
+    page[start..end]
+        .split_whitespace()
+        .map(|amount| {
+            let (unit_start, _) = amount.char_indices().last()?;
+            let (number, unit) = amount.split_at(unit_start);
+            let number = number.parse::<u64>().ok()?;
+
+            match unit {
+                "h" => Some(number * 3600),
+                "m" => Some(number * 60),
+                "s" => Some(number),
+                _ => None,
+            }
+        })
+        .sum()
+}
+
+/// The wait in a wrong answer page, e.g. `please wait 5 minutes before trying again.`
+fn parse_wrong_answer_wait(page: &str) -> Option<u64> {
+    let end = page.find(" before trying again")?;
+    let start = page[..end].rfind("wait ")? + "wait ".len();
+    let mut words = page[start..end].split_whitespace();
+    let number = match words.next()? {
+        "one" | "a" | "an" => 1,
+        number => number.parse::<u64>().ok()?,
+    };
+
+    match words.next()?.trim_end_matches('s') {
+        "hour" => Some(number * 3600),
+        "minute" => Some(number * 60),
+        "second" => Some(number),
+        _ => None,
+    }
+}
+
+impl History {
+    pub fn load(path: &Path) -> Result<Self, SiteError> {
+        json_file::load(path)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), SiteError> {
+        json_file::save(path, self)
+    }
+
+    /// Why an answer shouldn't be submitted, if the history already says it's wrong.
+    pub fn refusal(&self, day: u32, part: u32, answer: &str) -> Option<String> {
+        let wrong = self
+            .submissions
+            .iter()
+            .filter(|submission| submission.day == day && submission.part == part)
+            .filter(|submission| submission.outcome.is_wrong());
+
+        for submission in wrong {
+            if submission.answer == answer {
+                return Some(format!(
+                    "{} was already submitted: {}",
+                    answer, submission.outcome
+                ));
+            }
+
+            let bound = match (answer.parse::<i64>(), submission.answer.parse::<i64>()) {
+                (Ok(answer), Ok(submitted)) => match submission.outcome {
+                    Outcome::TooHigh if answer >= submitted => Some("too high"),
+                    Outcome::TooLow if answer <= submitted => Some("too low"),
+                    _ => None,
+                },
+                _ => None,
+            };
+
+            if let Some(bound) = bound {
+                return Some(format!(
+                    "{} can't be right, {} was already {}",
+                    answer, submission.answer, bound
+                ));
+            }
+        }
+
+        None
+    }
+}
+
+impl Submitter {
+    pub fn new(site: Site, history_path: &Path) -> Result<Self, SiteError> {
+        Ok(Submitter {
+            site,
+            history: History::load(history_path)?,
+            history_path: history_path.to_path_buf(),
+        })
+    }
+
+    pub fn history(&self) -> &History {
+        &self.history
+    }
+
+    /// Submits an answer unless the history says it's wrong or the site still wants
+    /// us to wait, then keeps the outcome in the history.
+    pub fn submit(&mut self, day: u32, part: u32, answer: &str) -> Result<Outcome, SiteError> {
+        if let Some(reason) = self.history.refusal(day, part, answer) {
+            return Err(SiteError::SubmissionRefused(reason));
+        }
+
+        let now = now();
+
+        if let Some(wait_until) = self.history.wait_until.filter(|&until| until > now) {
+            return Err(SiteError::SubmissionRefused(format!(
+                "the site asked to wait, try again in {}s",
+                wait_until - now
+            )));
+        }
+
+        let Reply { outcome, wait_secs } = self.site.submit(day, part, answer)?;
+        self.history.wait_until = wait_secs.map(|wait_secs| now + wait_secs);
+
+        if !matches!(outcome, Outcome::RateLimited { .. }) {
+            self.history.submissions.push(Submission {
+                day,
+                part,
+                answer: answer.to_string(),
+                outcome: outcome.clone(),
+                at: now,
+            });
+        }
+
+        self.history.save(&self.history_path)?;
+
+        Ok(outcome)
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|since| since.as_secs())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod submit_tests {
+    use super::*;
+    use crate::{
+        common::test_dir::TestDir,
+        site::{test_server::TestServer, UreqClient},
+    };
+    use anyhow::Result;
+
+    fn submitter(server: &TestServer, dir: &TestDir) -> Result<Submitter> {
+        let path = dir.join("submissions.json");
+        let site = Site::new(Box::new(UreqClient::new()), &server.url(), "abc", 2019);
+
+        Ok(Submitter::new(site, &path)?)
+    }
+
+    #[test]
+    fn parses_pages() {
+        assert_eq!(
+            Outcome::parse("<p>That's not the right answer; your answer is too high.</p>"),
+            Some(Outcome::TooHigh)
+        );
+        assert_eq!(
+            Outcome::parse("<p>You gave an answer too recently. You have 4m 52s left to wait.</p>"),
+            Some(Outcome::RateLimited { wait_secs: 292 })
+        );
+        assert_eq!(Outcome::parse("<p>Something else</p>"), None);
+        assert_eq!(parse_rate_limit("You have 4m 52ß left to wait."), None);
+    }
+
+    #[test]
+    fn waits_after_wrong_answers() {
+        let wait = |page| Reply::parse(page).and_then(|reply| reply.wait_secs);
+
+        assert_eq!(
+            wait("That's not the right answer. Please wait one minute before trying again."),
+            Some(60)
+        );
+        assert_eq!(
+            wait("That's not the right answer; please wait 5 minutes before trying again."),
+            Some(300)
+        );
+        assert_eq!(wait("That's not the right answer."), Some(60));
+        assert_eq!(wait("That's the right answer!"), None);
+    }
+
+    #[test]
+    fn refuses_known_wrong_answers() -> Result<()> {
+        let server = TestServer::start(|request| {
+            let page = if request.contains("answer=100") {
+                "That's not the right answer; your answer is too high."
+            } else {
+                "That's the right answer!"
+            };
+
+            (200, page.to_string())
+        })?;
+        let dir = TestDir::new("submit-wrong")?;
+        let mut submitter = submitter(&server, &dir)?;
+
+        assert_eq!(submitter.submit(1, 1, "100")?, Outcome::TooHigh);
+        assert!(submitter.submit(1, 1, "100").is_err());
+        assert!(submitter.submit(1, 1, "150").is_err());
+
+        // Anything else still has to wait for the site
+        assert!(submitter.submit(1, 1, "42").is_err());
+        submitter.history.wait_until = None;
+        assert_eq!(submitter.submit(1, 1, "42")?, Outcome::Correct);
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 2);
+        assert!(requests[0].starts_with("POST /2019/day/1/answer "));
+        assert!(requests[0].contains("level=1&answer=100"));
+        assert_eq!(submitter.history().submissions.len(), 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn waits_when_rate_limited() -> Result<()> {
+        let server = TestServer::start(|_| {
+            (
+                200,
+                "You gave an answer too recently. You have 30s left to wait.".to_string(),
+            )
+        })?;
+        let dir = TestDir::new("submit-rate")?;
+        let mut submitter = submitter(&server, &dir)?;
+
+        assert_eq!(
+            submitter.submit(2, 1, "1")?,
+            Outcome::RateLimited { wait_secs: 30 }
+        );
+        assert!(submitter.submit(2, 1, "2").is_err());
+        assert_eq!(server.requests().len(), 1);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,86 @@
+use std::{
+    io::{self, BufRead, BufReader, Read, Write},
+    net::{TcpListener, TcpStream},
+    sync::{Arc, Mutex},
+    thread,
+};
+
+type Handler = dyn Fn(&str) -> (u16, String) + Send + Sync;
+
+/// Stand-in for the puzzle site on a local port. Every request is kept, with its
+/// headers and body, and answered by the handler.
+pub struct TestServer {
+    port: u16,
+    requests: Arc<Mutex<Vec<String>>>,
+}
+
+impl TestServer {
+    pub fn start<F>(handler: F) -> io::Result<Self>
+    where
+        F: Fn(&str) -> (u16, String) + Send + Sync + 'static,
+    {
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        let port = listener.local_addr()?.port();
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let handler: Arc<Handler> = Arc::new(handler);
+        let server_requests = Arc::clone(&requests);
+
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                // A broken request just fails the test that sent it
+                let _ = respond(stream, &handler, &server_requests);
+            }
+        });
+
+        Ok(TestServer { port, requests })
+    }
+
+    pub fn url(&self) -> String {
+        format!("http://127.0.0.1:{}", self.port)
+    }
+
+    pub fn requests(&self) -> Vec<String> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+fn respond(
+    stream: TcpStream,
+    handler: &Arc<Handler>,
+    requests: &Mutex<Vec<String>>,
+) -> io::Result<()> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut request = String::new();
+    let mut content_length = 0;
+
+    loop {
+        let mut line = String::new();
+        reader.read_line(&mut line)?;
+
+        if let Some(length) = line.to_lowercase().strip_prefix("content-length:") {
+            content_length = length.trim().parse().unwrap_or(0);
+        }
+
+        request.push_str(&line);
+
+        if line == "\r\n" || line.is_empty() {
+            break;
+        }
+    }
+
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body)?;
+    request.push_str(&String::from_utf8_lossy(&body));
+
+    let (status, response) = handler(&request);
+    requests.lock().unwrap().push(request);
+
+    let mut stream = stream;
+    write!(
+        stream,
+        "HTTP/1.1 {} Test\r\nContent-Type: text/plain\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        response.len(),
+        response
+    )
+}
@@ -0,0 +1,337 @@
+use crate::day_runner::{answers::Verdict, DayRunner, PartResult, RunSummary};
+use anyhow::Result;
+use ratatui::{
+    crossterm::event::{self, Event, KeyCode, KeyEventKind},
+    layout::{Constraint, Layout},
+    style::{Style, Stylize},
+    widgets::{Block, Paragraph, Row, Table, TableState, Wrap},
+    DefaultTerminal, Frame,
+};
+
+const HELP: &str =
+    "↑/↓ select  enter run  1/2 run a part  a run all  v visualise  pgup/pgdn scroll  q quit";
+
+/// Shows every day in the runner's year and lets them be run from the keyboard, with
+/// whether each is done, whether its answers check out and how long it last took.
+pub fn run(runner: &DayRunner) -> Result<()> {
+    let mut terminal = ratatui::init();
+    let result = App::new(runner).run(&mut terminal);
+    ratatui::restore();
+
+    result
+}
+
+/// Something to run, which can take long enough to say so first.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Job {
+    Day(u32, Option<u32>),
+    All,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Pane {
+    Output,
+    Visualisation,
+}
+
+struct App<'r> {
+    runner: &'r DayRunner,
+    table: TableState,
+    /// The last run of each day, indexed by day - 1
+    runs: Vec<Option<Result<RunSummary, String>>>,
+    pane: Pane,
+    scroll: u16,
+    status: Option<String>,
+    quit: bool,
+}
+
+impl<'r> App<'r> {
+    fn new(runner: &'r DayRunner) -> Self {
+        App {
+            runner,
+            table: TableState::default().with_selected(Some(0)),
+            runs: (0..runner.puzzle_days()).map(|_| None).collect(),
+            pane: Pane::Output,
+            scroll: 0,
+            status: None,
+            quit: false,
+        }
+    }
+
+    fn run(&mut self, terminal: &mut DefaultTerminal) -> Result<()> {
+        while !self.quit {
+            terminal.draw(|frame| self.draw(frame))?;
+
+            let code = match event::read()? {
+                Event::Key(key) if key.kind == KeyEventKind::Press => key.code,
+                _ => continue,
+            };
+
+            if let Some(job) = self.handle_key(code) {
+                self.status = Some(match job {
+                    Job::Day(day, _) => format!("Running day {}...", day),
+                    Job::All => "Running every day...".to_string(),
+                });
+                terminal.draw(|frame| self.draw(frame))?;
+                self.run_job(job);
+                self.status = None;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn selected_day(&self) -> u32 {
+        self.table.selected().unwrap_or(0) as u32 + 1
+    }
+
+    fn handle_key(&mut self, code: KeyCode) -> Option<Job> {
+        let day = self.selected_day();
+
+        match code {
+            KeyCode::Char('q') => self.quit = true,
+            KeyCode::Esc if self.pane == Pane::Visualisation => self.pane = Pane::Output,
+            KeyCode::Esc => self.quit = true,
+            KeyCode::Up | KeyCode::Char('k') => self.select(day.saturating_sub(1).max(1)),
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.select((day + 1).min(self.runner.puzzle_days()))
+            }
+            KeyCode::PageUp => self.scroll = self.scroll.saturating_sub(10),
+            KeyCode::PageDown => self.scroll = self.scroll.saturating_add(10),
+            KeyCode::Char('v') => {
+                self.pane = match self.pane {
+                    Pane::Output => Pane::Visualisation,
+                    Pane::Visualisation => Pane::Output,
+                };
+                self.scroll = 0;
+            }
+            KeyCode::Enter | KeyCode::Char('r') => return Some(Job::Day(day, None)),
+            KeyCode::Char('1') => return Some(Job::Day(day, Some(1))),
+            KeyCode::Char('2') => return Some(Job::Day(day, Some(2))),
+            KeyCode::Char('a') => return Some(Job::All),
+            _ => {}
+        }
+
+        None
+    }
+
+    fn select(&mut self, day: u32) {
+        self.table.select(Some(day as usize - 1));
+        self.scroll = 0;
+    }
+
+    fn run_job(&mut self, job: Job) {
+        let days = match job {
+            Job::Day(day, part) => vec![(day, part)],
+            Job::All => (1..=self.runner.puzzle_days())
+                .filter(|&day| self.runner.is_registered(day))
+                .map(|day| (day, None))
+                .collect(),
+        };
+
+        for (day, part) in days {
+            let run = self
+                .runner
+                .run_selected(day, part)
+                .map_err(|e| format!("{:#}", e));
+            self.runs[day as usize - 1] = Some(run);
+        }
+
+        self.pane = Pane::Output;
+        self.scroll = 0;
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [main, footer] =
+            Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).areas(frame.area());
+        let [list, detail] =
+            Layout::horizontal([Constraint::Length(34), Constraint::Min(0)]).areas(main);
+
+        let rows = (1..=self.runner.puzzle_days()).map(|day| {
+            let (check, last) = match &self.runs[day as usize - 1] {
+                Some(Ok(summary)) => (check(&summary.parts), format!("{:.2?}", summary.total)),
+                Some(Err(_)) => ("error", String::new()),
+                None => ("", String::new()),
+            };
+            let done = match &self.runs[day as usize - 1] {
+                _ if !self.runner.is_registered(day) => "-",
+                Some(Ok(summary)) => done(&summary.parts),
+                _ => "",
+            };
+
+            Row::new(vec![
+                day.to_string(),
+                done.to_string(),
+                check.to_string(),
+                last,
+            ])
+        });
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Length(3),
+                Constraint::Length(4),
+                Constraint::Length(8),
+                Constraint::Length(10),
+            ],
+        )
+        .header(Row::new(vec!["Day", "Done", "Check", "Last"]).bold())
+        .block(Block::bordered().title(" Days "))
+        .row_highlight_style(Style::new().reversed());
+
+        frame.render_stateful_widget(table, list, &mut self.table);
+
+        let day = self.selected_day();
+        let (title, text) = match self.pane {
+            Pane::Output => (format!(" Day {} ", day), self.output(day)),
+            Pane::Visualisation => (
+                format!(" Day {} visualisation ", day),
+                self.visualisation(day),
+            ),
+        };
+        let detail_text = Paragraph::new(text)
+            .block(Block::bordered().title(title))
+            .wrap(Wrap { trim: false })
+            .scroll((self.scroll, 0));
+
+        frame.render_widget(detail_text, detail);
+        frame.render_widget(
+            Paragraph::new(self.status.as_deref().unwrap_or(HELP)).dim(),
+            footer,
+        );
+    }
+
+    fn output(&self, day: u32) -> String {
+        if !self.runner.is_registered(day) {
+            return "I haven't got to that day yet!".to_string();
+        }
+
+        let summary = match &self.runs[day as usize - 1] {
+            Some(Ok(summary)) => summary,
+            Some(Err(e)) => return e.clone(),
+            None => return "Press enter to run both parts, or 1 or 2 for just one".to_string(),
+        };
+
+        summary
+            .parts
+            .iter()
+            .map(|result| {
+                let answer = match (&result.answer, &result.verdict) {
+                    (Ok(answer), Verdict::Unknown) => answer.to_string(),
+                    (Ok(answer), verdict) => format!("{} ({})", answer, verdict),
+                    (Err(_), _) if result.is_not_done() => "not done".to_string(),
+                    (Err(e), _) => format!("error: {}", e),
+                };
+
+                format!(
+                    "Part {}\n{}\ninput {:.2?}, parse {:.2?}, solve {:.2?}\n",
+                    result.part, answer, result.input.mean, result.parse.mean, result.solve.mean
+                )
+            })
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+
+    fn visualisation(&self, day: u32) -> String {
+        match self.runner.visualise(day) {
+            Ok(Some(picture)) => picture,
+            Ok(None) => format!("Day {} has nothing to show", day),
+            Err(e) => e.to_string(),
+        }
+    }
+}
+
+/// Whether the parts that were run have been done, going by what they gave back.
+fn done(parts: &[PartResult]) -> &'static str {
+    let not_done = parts.iter().filter(|part| part.is_not_done()).count();
+
+    if not_done == 0 {
+        "yes"
+    } else if not_done < parts.len() {
+        "part"
+    } else {
+        "no"
+    }
+}
+
+/// The worst verdict of the parts, errors included. Parts that aren't done yet have
+/// nothing to check.
+fn check(parts: &[PartResult]) -> &'static str {
+    if parts
+        .iter()
+        .any(|part| part.answer.is_err() && !part.is_not_done())
+    {
+        "error"
+    } else if parts
+        .iter()
+        .any(|part| matches!(part.verdict, Verdict::Wrong { .. }))
+    {
+        "wrong"
+    } else if parts.iter().any(|part| part.is_not_done()) {
+        "not done"
+    } else if parts.iter().all(|part| part.verdict == Verdict::Correct) {
+        "correct"
+    } else {
+        "unknown"
+    }
+}
+
+#[cfg(test)]
+mod tui_tests {
+    use super::*;
+    use crate::{
+        day_runner::answers::Answers,
+        days::{answer::Answer, CommonError},
+        years,
+    };
+    use ratatui::{backend::TestBackend, Terminal};
+
+    #[test]
+    fn runs_selected_day() -> Result<()> {
+        let answers = Answers::load(&years::default().answers_path())?;
+        let runner = DayRunner::new()?.with_answers(answers);
+        let mut app = App::new(&runner);
+        let mut terminal = Terminal::new(TestBackend::new(100, 30))?;
+
+        app.handle_key(KeyCode::Down);
+        app.handle_key(KeyCode::Up);
+        let job = app.handle_key(KeyCode::Enter);
+        assert_eq!(job, Some(Job::Day(1, None)));
+        app.run_job(Job::Day(1, None));
+        terminal.draw(|frame| app.draw(frame))?;
+
+        let screen = terminal
+            .backend()
+            .buffer()
+            .content()
+            .iter()
+            .map(|cell| cell.symbol())
+            .collect::<String>();
+
+        assert!(screen.contains("3443395 (correct)"), "{}", screen);
+        assert!(screen.contains("correct"));
+
+        app.handle_key(KeyCode::Char('q'));
+        assert!(app.quit);
+
+        Ok(())
+    }
+
+    #[test]
+    fn not_done_parts_are_not_errors() {
+        let parts = vec![
+            PartResult::sample(1, 1, Ok(Answer::Integer(1))),
+            PartResult::sample(1, 2, Err(CommonError::NotDone.into())),
+        ];
+
+        assert_eq!(done(&parts), "part");
+        assert_eq!(check(&parts), "not done");
+        assert_eq!(done(&parts[..1]), "yes");
+        assert_eq!(done(&parts[1..]), "no");
+
+        let failed = vec![PartResult::sample(1, 1, Err(anyhow::anyhow!("Oops")))];
+
+        assert_eq!(done(&failed), "yes");
+        assert_eq!(check(&failed), "error");
+    }
+}
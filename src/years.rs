@@ -0,0 +1,113 @@
+use crate::days::{self, input::InputSource, DayEntry};
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+pub const DEFAULT_YEAR: u32 = 2019;
+
+/// Every event in the workspace. Another year is a module like `days`, with its own
+/// `days!` registry, and a line here.
+pub const YEARS: &[Year] = &[Year {
+    year: 2019,
+    puzzle_days: 25,
+    days: days::DAYS,
+    module: "days",
+    res_dir: "res",
+}];
+
+#[derive(Debug, Error)]
+pub enum YearError {
+    #[error("There's nothing for {0} yet, expected one of: {1}")]
+    UnknownYear(u32, String),
+}
+
+/// One year's event: its days, and where its code and files live.
+pub struct Year {
+    pub year: u32,
+    /// Days in the event, including the ones that aren't done yet
+    pub puzzle_days: u32,
+    pub days: &'static [DayEntry],
+    /// Module holding the year's days and their registry, under `src`
+    pub module: &'static str,
+    /// Directory holding the year's inputs, answers, examples and submissions
+    pub res_dir: &'static str,
+}
+
+impl Year {
+    pub fn input_source(&self) -> InputSource {
+        InputSource::Directory(PathBuf::from(self.res_dir))
+    }
+
+    pub fn answers_path(&self) -> PathBuf {
+        Path::new(self.res_dir).join("answers.json")
+    }
+
+    pub fn examples_dir(&self) -> PathBuf {
+        Path::new(self.res_dir).join("examples")
+    }
+
+    pub fn history_path(&self) -> PathBuf {
+        Path::new(self.res_dir).join("submissions.json")
+    }
+
+    /// Timings from every run, which are only meaningful on the machine they're from.
+    pub fn timings_path(&self) -> PathBuf {
+        Path::new(self.res_dir).join("timings.json")
+    }
+}
+
+pub fn find(year: u32) -> Result<&'static Year, YearError> {
+    YEARS
+        .iter()
+        .find(|entry| entry.year == year)
+        .ok_or_else(|| {
+            let known = YEARS
+                .iter()
+                .map(|entry| entry.year.to_string())
+                .collect::<Vec<String>>();
+
+            YearError::UnknownYear(year, known.join(", "))
+        })
+}
+
+pub fn default() -> &'static Year {
+    // The default year is always registered
+    find(DEFAULT_YEAR).unwrap()
+}
+
+/// The year whose inputs are kept in the directory, if any.
+pub fn for_input_dir(dir: &Path) -> Option<&'static Year> {
+    let dir = dir.canonicalize().unwrap_or_else(|_| dir.to_path_buf());
+
+    YEARS.iter().find(|entry| {
+        let res_dir = Path::new(entry.res_dir);
+
+        res_dir
+            .canonicalize()
+            .unwrap_or_else(|_| res_dir.to_path_buf())
+            == dir
+    })
+}
+
+#[cfg(test)]
+mod years_tests {
+    use super::*;
+    use anyhow::Result;
+
+    #[test]
+    fn finds_years_by_input_dir() -> Result<()> {
+        let year = find(DEFAULT_YEAR)?;
+
+        assert_eq!(
+            for_input_dir(Path::new("res")).map(|y| y.year),
+            Some(year.year)
+        );
+        assert_eq!(
+            for_input_dir(Path::new("./res")).map(|y| y.year),
+            Some(year.year)
+        );
+        assert!(for_input_dir(Path::new("src")).is_none());
+        assert!(find(2015).is_err());
+
+        Ok(())
+    }
+}